@@ -0,0 +1,13 @@
+#!/bin/sh
+# Day 1 as a plugin, to try out the protocol: `aoc2021 run 1 --plugin plugins/day1.sh`.
+# Called with the day and part, reads the depths on stdin and prints the answer as JSON.
+case "$2" in
+    1) size=1 ;;
+    2) size=3 ;;
+    *) echo "unknown part $2" >&2; exit 1 ;;
+esac
+awk -v size="$size" -v part="$2" '
+    { depths[NR] = $1 }
+    NR > size && depths[NR] > depths[NR - size] { increases++ }
+    END { printf "{\"part%d\": %d}\n", part, increases }
+'
@@ -0,0 +1,88 @@
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+thread_local! {
+    static COUNT: Cell<u64> = const { Cell::new(0) };
+    static BYTES: Cell<u64> = const { Cell::new(0) };
+}
+
+/// Wraps the system allocator to count allocations per thread, so concurrent solvers do not
+/// see each other's allocations.
+#[derive(Debug)]
+pub struct CountingAllocator;
+
+fn record(size: usize) {
+    // the thread locals are gone while a thread shuts down, those allocations are not ours
+    let _ = COUNT.try_with(|count| count.set(count.get() + 1));
+    let _ = BYTES.try_with(|bytes| bytes.set(bytes.get() + size as u64));
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        record(layout.size());
+        System.alloc(layout)
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        record(layout.size());
+        System.alloc_zeroed(layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        record(new_size);
+        System.realloc(ptr, layout, new_size)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+/// How often and how much a piece of code allocated, reallocations count as new allocations.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Allocations {
+    pub count: u64,
+    pub bytes: u64,
+}
+
+fn current() -> Allocations {
+    Allocations {
+        count: COUNT.with(Cell::get),
+        bytes: BYTES.with(Cell::get),
+    }
+}
+
+/// Runs `f` and returns what it allocated on the current thread. Only counts anything when
+/// `CountingAllocator` is the global allocator.
+pub fn measure<T>(f: impl FnOnce() -> T) -> (T, Allocations) {
+    let before = current();
+    let result = f();
+    let after = current();
+    (
+        result,
+        Allocations {
+            count: after.count - before.count,
+            bytes: after.bytes - before.bytes,
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_measure() {
+        let (v, allocations) = measure(|| vec![0u8; 100]);
+        assert_eq!(v.len(), 100);
+        assert_eq!(
+            allocations,
+            Allocations {
+                count: 1,
+                bytes: 100
+            }
+        );
+        let (_, allocations) = measure(|| 1 + 1);
+        assert_eq!(allocations, Allocations::default());
+    }
+}
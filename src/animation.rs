@@ -0,0 +1,201 @@
+use crate::utils::terminal::{RawMode, CLEAR_SCREEN, HIDE_CURSOR, SHOW_CURSOR};
+use std::io::{self, Read, Write};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+pub trait Simulation {
+    /// Renders the current state of the simulation as plain text.
+    fn frame(&self) -> String;
+
+    /// Advances the simulation by one step, returns false once there is nothing left to do.
+    fn step(&mut self) -> bool;
+}
+
+#[derive(Debug, Eq, PartialEq)]
+enum Key {
+    Pause,
+    Step,
+    Quit,
+}
+
+impl Key {
+    /// Splits what one read from the terminal returned into keys. Arrow keys and the like arrive
+    /// as escape sequences, those are skipped so only escape on its own quits.
+    fn parse(bytes: &[u8]) -> Vec<Key> {
+        let mut keys = Vec::new();
+        let mut rest = bytes;
+        while !rest.is_empty() {
+            let (key, len) = match rest {
+                [0x1b, b'[', sequence @ ..] => {
+                    let end = sequence
+                        .iter()
+                        .position(|byte| (0x40..=0x7e).contains(byte))
+                        .map_or(sequence.len(), |index| index + 1);
+                    (None, 2 + end)
+                }
+                [b' ' | b'p', ..] => (Some(Key::Pause), 1),
+                [b's' | b'n', ..] => (Some(Key::Step), 1),
+                [b'q' | 0x1b, ..] => (Some(Key::Quit), 1),
+                [_, ..] => (None, 1),
+                [] => unreachable!(),
+            };
+            keys.extend(key);
+            rest = &rest[len..];
+        }
+        keys
+    }
+}
+
+fn spawn_key_reader() -> Receiver<Key> {
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let mut stdin = io::stdin();
+        let mut buffer = [0u8; 16];
+        while let Ok(len @ 1..) = stdin.read(&mut buffer) {
+            for key in Key::parse(&buffer[..len]) {
+                if sender.send(key).is_err() {
+                    return;
+                }
+            }
+        }
+    });
+    receiver
+}
+
+#[derive(Debug)]
+pub struct Animator {
+    frame_time: Duration,
+}
+
+impl Animator {
+    pub fn new(fps: f64) -> Self {
+        Self {
+            frame_time: Duration::from_secs_f64(1.0 / fps.max(0.1)),
+        }
+    }
+
+    /// Plays the simulation on the terminal. Space pauses, `s` steps while paused and `q` quits.
+    pub fn play(&self, simulation: &mut dyn Simulation) -> io::Result<()> {
+        let _raw_mode = RawMode::enable();
+        let keys = spawn_key_reader();
+        let mut stdout = io::stdout();
+        let mut paused = false;
+        let mut step = 0;
+        write!(stdout, "{}", HIDE_CURSOR)?;
+        let result = loop {
+            draw(&mut stdout, simulation, step, paused)?;
+            let deadline = Instant::now() + self.frame_time;
+            let quit = loop {
+                let key = if paused {
+                    keys.recv().map_err(|_| RecvTimeoutError::Disconnected)
+                } else {
+                    keys.recv_timeout(deadline.saturating_duration_since(Instant::now()))
+                };
+                match key {
+                    Ok(Key::Quit) => break true,
+                    Ok(Key::Pause) => {
+                        paused = !paused;
+                        draw(&mut stdout, simulation, step, paused)?;
+                    }
+                    Ok(Key::Step) if paused => break false,
+                    Ok(Key::Step) => (),
+                    Err(RecvTimeoutError::Timeout) => break false,
+                    Err(RecvTimeoutError::Disconnected) => {
+                        // no keyboard to read from, keep playing at the configured frame rate
+                        paused = false;
+                        thread::sleep(deadline.saturating_duration_since(Instant::now()));
+                        break false;
+                    }
+                }
+            };
+            if quit {
+                break Ok(());
+            }
+            if !simulation.step() {
+                draw(&mut stdout, simulation, step + 1, paused)?;
+                break Ok(());
+            }
+            step += 1;
+        };
+        writeln!(stdout, "{}", SHOW_CURSOR)?;
+        result
+    }
+}
+
+fn draw(
+    out: &mut impl Write,
+    simulation: &dyn Simulation,
+    step: usize,
+    paused: bool,
+) -> io::Result<()> {
+    write!(
+        out,
+        "{}{}",
+        CLEAR_SCREEN,
+        simulation.frame().replace('\n', "\r\n")
+    )?;
+    write!(
+        out,
+        "\r\nstep {}{}  [space] pause  [s] step  [q] quit\r\n",
+        step,
+        if paused { " (paused)" } else { "" }
+    )?;
+    out.flush()
+}
+
+/// Runs the simulation to completion without a terminal, writing every frame to `out`.
+/// Returns the number of frames written.
+pub fn record(simulation: &mut dyn Simulation, mut out: impl Write) -> io::Result<usize> {
+    let mut frames = 0;
+    loop {
+        writeln!(out, "--- frame {} ---", frames)?;
+        writeln!(out, "{}", simulation.frame())?;
+        frames += 1;
+        if !simulation.step() {
+            break;
+        }
+    }
+    out.flush()?;
+    Ok(frames)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Countdown(u32);
+
+    impl Simulation for Countdown {
+        fn frame(&self) -> String {
+            self.0.to_string()
+        }
+
+        fn step(&mut self) -> bool {
+            if self.0 == 0 {
+                false
+            } else {
+                self.0 -= 1;
+                true
+            }
+        }
+    }
+
+    #[test]
+    fn test_record() {
+        let mut out = Vec::new();
+        assert_eq!(record(&mut Countdown(2), &mut out).unwrap(), 3);
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "--- frame 0 ---\n2\n--- frame 1 ---\n1\n--- frame 2 ---\n0\n"
+        );
+    }
+
+    #[test]
+    fn test_keys() {
+        assert_eq!(Key::parse(b" sqx"), [Key::Pause, Key::Step, Key::Quit]);
+        // arrow and page keys do nothing, a lone escape quits
+        assert_eq!(Key::parse(b"\x1b[A\x1b[6~\x1b[1;5Cn"), [Key::Step]);
+        assert_eq!(Key::parse(b"\x1b"), [Key::Quit]);
+    }
+}
@@ -0,0 +1,108 @@
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+pub const DEFAULT_DIR: &str = ".aoc-cache";
+
+/// FNV-1a, stable across builds and platforms unlike the std hasher.
+pub fn hash(input: &str) -> u64 {
+    fnv(input.bytes())
+}
+
+fn fnv(bytes: impl Iterator<Item = u8>) -> u64 {
+    bytes.fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// Stored with each answer to tell inputs apart whose hashes collide: the length and a hash of
+/// the input backwards.
+fn check(input: &str) -> String {
+    format!("{} {:016x}", input.len(), fnv(input.bytes().rev()))
+}
+
+/// Answers stored on disk, keyed by day, part, solver version and a hash of the input. Each
+/// file holds a check of the input on its first line and the answer after it.
+#[derive(Debug, Clone)]
+pub struct Cache {
+    dir: PathBuf,
+}
+
+impl Cache {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    fn path(&self, day: u8, part: u8, version: u32, input: &str) -> PathBuf {
+        self.dir.join(format!(
+            "day{}-part{}-v{}-{:016x}",
+            day,
+            part,
+            version,
+            hash(input)
+        ))
+    }
+
+    pub fn get(&self, day: u8, part: u8, version: u32, input: &str) -> Option<String> {
+        let stored = fs::read_to_string(self.path(day, part, version, input)).ok()?;
+        let (check_line, answer) = stored.split_once('\n')?;
+        (check_line == check(input)).then(|| answer.to_owned())
+    }
+
+    pub fn put(
+        &self,
+        day: u8,
+        part: u8,
+        version: u32,
+        input: &str,
+        answer: &str,
+    ) -> io::Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        let stored = format!("{}\n{}", check(input), answer);
+        fs::write(self.path(day, part, version, input), stored)
+    }
+
+    pub fn clear(&self) -> io::Result<()> {
+        match fs::remove_dir_all(&self.dir) {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => Err(err),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hash() {
+        assert_eq!(hash(""), 0xcbf29ce484222325);
+        assert_eq!(hash("a"), 0xaf63dc4c8601ec8c);
+        assert_ne!(hash("1\n2"), hash("2\n1"));
+    }
+
+    #[test]
+    fn test_cache() {
+        let dir = std::env::temp_dir().join(format!("aoc2021-cache-{}", std::process::id()));
+        let cache = Cache::new(&dir);
+        cache.clear().unwrap();
+        assert_eq!(cache.get(1, 1, 1, "1\n2"), None);
+        cache.put(1, 1, 1, "1\n2", "1").unwrap();
+        assert_eq!(cache.get(1, 1, 1, "1\n2"), Some("1".to_owned()));
+        assert_eq!(cache.get(1, 2, 1, "1\n2"), None);
+        assert_eq!(cache.get(1, 1, 2, "1\n2"), None);
+        assert_eq!(cache.get(1, 1, 1, "1\n3"), None);
+
+        // an input with the same hash as a cached one, or a file from before the check
+        let path = cache.path(1, 1, 1, "1\n2");
+        fs::write(&path, format!("3 {:016x}\n1", hash("1\n2"))).unwrap();
+        assert_eq!(cache.get(1, 1, 1, "1\n2"), None);
+        fs::write(&path, "1").unwrap();
+        assert_eq!(cache.get(1, 1, 1, "1\n2"), None);
+        cache.put(1, 1, 1, "1\n2", "a\nb").unwrap();
+        assert_eq!(cache.get(1, 1, 1, "1\n2"), Some("a\nb".to_owned()));
+        cache.clear().unwrap();
+        assert_eq!(cache.get(1, 1, 1, "1\n2"), None);
+        assert!(!dir.exists());
+    }
+}
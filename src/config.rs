@@ -0,0 +1,302 @@
+use crate::utils::parse::{integer, ParseError, ParseResult, Separator, Span};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::Duration;
+
+/// Looked for in the current directory and its parents.
+pub const FILE_NAME: &str = "aoc.toml";
+/// Looked for in the home directory when there is no project config.
+pub const HOME_FILE_NAME: &str = ".aoc.toml";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Table,
+    Json,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "table" => Ok(Format::Table),
+            "json" => Ok(Format::Json),
+            _ => Err(format!("expected \"table\" or \"json\", found {:?}", s)),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Io(PathBuf, io::Error),
+    Parse(PathBuf, ParseError),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Io(path, err) => write!(f, "could not read {}: {}", path.display(), err),
+            ConfigError::Parse(path, err) => write!(f, "{}: {}", path.display(), err),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+/// Settings from an `aoc.toml` file, a small subset of TOML:
+///
+/// ```toml
+/// year = 2021
+/// inputs = "inputs"      # one input file or directory of inputs per day, named by the day
+/// examples = "examples"
+/// format = "json"        # or "table"
+/// timeout = 2.5          # seconds per part
+/// session = "~/.config/aoc/session"
+///
+/// [bench]
+/// iterations = 20
+///
+/// [plugins]              # external solvers, by day
+/// 3 = "plugins/day3.py"
+/// ```
+///
+/// Relative paths are relative to the config file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Config {
+    pub year: u16,
+    pub inputs: Option<PathBuf>,
+    pub examples: Option<PathBuf>,
+    pub format: Format,
+    pub timeout: Option<Duration>,
+    /// A file holding the adventofcode.com session cookie, for tools that download inputs.
+    pub session: Option<PathBuf>,
+    pub bench_iterations: u32,
+    /// Executables that solve a day instead of the built in solver, see `plugin`.
+    pub plugins: BTreeMap<u8, PathBuf>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            year: 2021,
+            inputs: None,
+            examples: None,
+            format: Format::Table,
+            timeout: None,
+            session: None,
+            bench_iterations: 10,
+            plugins: BTreeMap::new(),
+        }
+    }
+}
+
+fn string(span: Span) -> ParseResult<String> {
+    let text = span.as_str();
+    let mut value = String::new();
+    let mut chars = text.char_indices().skip(1);
+    if !text.starts_with('"') {
+        return Err(span.error(format!("expected a quoted string, found {:?}", text)));
+    }
+    while let Some((index, c)) = chars.next() {
+        match c {
+            '"' if index + 1 == text.len() => return Ok(value),
+            '"' => break,
+            '\\' => match chars.next() {
+                Some((_, c @ ('"' | '\\'))) => value.push(c),
+                _ => return Err(span.error("only \\\" and \\\\ escapes are supported")),
+            },
+            c => value.push(c),
+        }
+    }
+    Err(span.error(format!("expected a quoted string, found {:?}", text)))
+}
+
+fn seconds(span: Span) -> ParseResult<Duration> {
+    match span.as_str().parse::<f64>() {
+        Ok(seconds) if seconds.is_finite() && seconds >= 0.0 => {
+            Ok(Duration::from_secs_f64(seconds))
+        }
+        _ => Err(span.error(format!("expected seconds, found {:?}", span.as_str()))),
+    }
+}
+
+/// Strips a trailing comment, unless the `#` is inside a string.
+fn strip_comment(span: Span) -> Span {
+    let mut quoted = false;
+    let mut escaped = false;
+    for (index, c) in span.as_str().char_indices() {
+        match c {
+            '#' if !quoted => return span.split_at(index).0,
+            '"' if !escaped => quoted = !quoted,
+            _ => (),
+        }
+        escaped = c == '\\' && !escaped;
+    }
+    span
+}
+
+impl FromStr for Config {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut config = Config::default();
+        let mut section = "";
+        for line in Span::new(s).split(Separator::Lines) {
+            let line = strip_comment(line).trim();
+            if line.is_empty() {
+                continue;
+            }
+            if let Some(name) = line
+                .as_str()
+                .strip_prefix('[')
+                .and_then(|name| name.strip_suffix(']'))
+            {
+                section = name.trim();
+                continue;
+            }
+            let (key, value) = match line.split_once("=") {
+                Some((key, value)) => (key.trim(), value.trim()),
+                None => return Err(line.error("expected \"key = value\"")),
+            };
+            match (section, key.as_str()) {
+                ("", "year") => config.year = integer(value)?,
+                ("", "inputs") => config.inputs = Some(string(value)?.into()),
+                ("", "examples") => config.examples = Some(string(value)?.into()),
+                ("", "format") => {
+                    config.format = string(value)?.parse().map_err(|err| value.error(err))?
+                }
+                ("", "timeout") => config.timeout = Some(seconds(value)?),
+                ("", "session") => config.session = Some(string(value)?.into()),
+                ("bench", "iterations") => config.bench_iterations = integer(value)?,
+                ("plugins", _) => {
+                    config.plugins.insert(integer(key)?, string(value)?.into());
+                }
+                ("", key) => return Err(line.error(format!("unknown setting {:?}", key))),
+                (section, key) => {
+                    return Err(line.error(format!("unknown setting {:?} in [{}]", key, section)))
+                }
+            }
+        }
+        Ok(config)
+    }
+}
+
+fn resolve(path: &Path, base: &Path, home: Option<&Path>) -> PathBuf {
+    match (path.strip_prefix("~"), home) {
+        (Ok(rest), Some(home)) => home.join(rest),
+        _ => base.join(path),
+    }
+}
+
+fn home() -> Option<PathBuf> {
+    std::env::var_os("HOME").map(PathBuf::from)
+}
+
+impl Config {
+    /// Reads a config file, resolving its paths against the directory it is in.
+    pub fn read(path: &Path) -> Result<Self, ConfigError> {
+        let text = fs::read_to_string(path).map_err(|err| ConfigError::Io(path.to_owned(), err))?;
+        let mut config: Config = text
+            .parse()
+            .map_err(|err| ConfigError::Parse(path.to_owned(), err))?;
+        let base = path.parent().unwrap_or_else(|| Path::new(""));
+        let home = home();
+        for path in [
+            &mut config.inputs,
+            &mut config.examples,
+            &mut config.session,
+        ]
+        .into_iter()
+        .flatten()
+        .chain(config.plugins.values_mut())
+        {
+            *path = resolve(path, base, home.as_deref());
+        }
+        Ok(config)
+    }
+
+    /// The config file that applies to the current directory, if there is one.
+    pub fn find() -> Option<PathBuf> {
+        let cwd = std::env::current_dir().ok()?;
+        cwd.ancestors()
+            .map(|dir| dir.join(FILE_NAME))
+            .chain(home().map(|home| home.join(HOME_FILE_NAME)))
+            .find(|path| path.is_file())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let config: Config = "# settings for this checkout
+year = 2022
+inputs = \"data/inputs\"   # per day
+format = \"json\"
+timeout = 1.5
+session = \"~/.aoc \\\"session\\\" #1\"
+
+[bench]
+iterations = 3
+
+[plugins]
+4 = \"plugins/day4.sh\"
+"
+        .parse()
+        .unwrap();
+        assert_eq!(
+            config,
+            Config {
+                year: 2022,
+                inputs: Some("data/inputs".into()),
+                examples: None,
+                format: Format::Json,
+                timeout: Some(Duration::from_millis(1500)),
+                session: Some("~/.aoc \"session\" #1".into()),
+                bench_iterations: 3,
+                plugins: BTreeMap::from([(4, "plugins/day4.sh".into())]),
+            }
+        );
+        assert_eq!("".parse::<Config>().unwrap(), Config::default());
+    }
+
+    #[test]
+    fn test_parse_error() {
+        let err = "year = 2021\nformat = \"xml\""
+            .parse::<Config>()
+            .unwrap_err();
+        assert_eq!((err.line, err.column), (2, 10));
+        assert_eq!(err.message, "expected \"table\" or \"json\", found \"xml\"");
+        let err = "[bench]\nyear = 2021".parse::<Config>().unwrap_err();
+        assert_eq!(err.message, "unknown setting \"year\" in [bench]");
+        let err = "inputs = data".parse::<Config>().unwrap_err();
+        assert_eq!((err.line, err.column), (1, 10));
+        let err = "inputs = \"a\" \"b\"".parse::<Config>().unwrap_err();
+        assert_eq!((err.line, err.column), (1, 10));
+        let err = "timeout".parse::<Config>().unwrap_err();
+        assert_eq!(err.message, "expected \"key = value\"");
+    }
+
+    #[test]
+    fn test_resolve() {
+        let home = Path::new("/home/me");
+        assert_eq!(
+            resolve(Path::new("inputs"), Path::new("/project"), Some(home)),
+            Path::new("/project/inputs")
+        );
+        assert_eq!(
+            resolve(Path::new("/abs"), Path::new("/project"), Some(home)),
+            Path::new("/abs")
+        );
+        assert_eq!(
+            resolve(Path::new("~/session"), Path::new("/project"), Some(home)),
+            Path::new("/home/me/session")
+        );
+    }
+}
@@ -0,0 +1,782 @@
+//! A full screen terminal view of every day, its answers and timings. Parts are solved one at a
+//! time on a worker thread so the view stays usable while they run, traces and grids are
+//! rendered on one too.
+
+use crate::runner::{self, Input, Options, Outcome, Status};
+use crate::solvers::Solver;
+use crate::utils::terminal::{
+    self, RawMode, ENTER_ALTERNATE_SCREEN, HIDE_CURSOR, LEAVE_ALTERNATE_SCREEN, SHOW_CURSOR,
+};
+use std::collections::{HashMap, VecDeque};
+use std::io::{self, Read, Write};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+
+/// Used when the size of the terminal cannot be found out.
+const DEFAULT_SIZE: (usize, usize) = (80, 24);
+/// How far the page keys scroll the detail pane.
+const PAGE: usize = 10;
+
+const CURSOR_HOME: &str = "\x1b[H";
+const RESET: &str = "\x1b[0m";
+const BOLD: &str = "\x1b[1m";
+const DIM: &str = "\x1b[2m";
+const REVERSE: &str = "\x1b[7m";
+const RED: &str = "\x1b[31m";
+const GREEN: &str = "\x1b[32m";
+
+const HELP: &str = "up/down day  left/right input  1/2 re-run part  r re-run both  t trace  g grid  pgup/pgdn scroll  q quit";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Key {
+    Up,
+    Down,
+    Left,
+    Right,
+    PageUp,
+    PageDown,
+    Char(char),
+}
+
+impl Key {
+    /// Splits what one read from the terminal returned into keys, the arrow and page keys arrive
+    /// as escape sequences. Sequences for other keys are skipped.
+    fn parse(bytes: &[u8]) -> Vec<Key> {
+        let mut keys = Vec::new();
+        let mut rest = bytes;
+        while !rest.is_empty() {
+            let (key, len) = match rest {
+                [0x1b, b'[', b'A', ..] => (Some(Key::Up), 3),
+                [0x1b, b'[', b'B', ..] => (Some(Key::Down), 3),
+                [0x1b, b'[', b'C', ..] => (Some(Key::Right), 3),
+                [0x1b, b'[', b'D', ..] => (Some(Key::Left), 3),
+                [0x1b, b'[', b'5', b'~', ..] => (Some(Key::PageUp), 4),
+                [0x1b, b'[', b'6', b'~', ..] => (Some(Key::PageDown), 4),
+                [0x1b, b'[', sequence @ ..] => {
+                    let end = sequence
+                        .iter()
+                        .position(|byte| (0x40..=0x7e).contains(byte))
+                        .map_or(sequence.len(), |index| index + 1);
+                    (None, 2 + end)
+                }
+                [byte, ..] => (Some(Key::Char(*byte as char)), 1),
+                [] => unreachable!(),
+            };
+            keys.extend(key);
+            rest = &rest[len..];
+        }
+        keys
+    }
+}
+
+/// What the event loop waits for.
+#[derive(Debug)]
+enum Event {
+    Key(Key),
+    Solved(Job, Outcome),
+    /// The lines of a pane, for the day and input it was opened on.
+    Rendered {
+        view: View,
+        day: usize,
+        input: usize,
+        lines: Vec<String>,
+    },
+    /// The keyboard is gone.
+    Closed,
+}
+
+fn spawn_key_reader(sender: Sender<Event>) {
+    thread::spawn(move || {
+        let mut stdin = io::stdin();
+        let mut buffer = [0u8; 16];
+        while let Ok(len @ 1..) = stdin.read(&mut buffer) {
+            for key in Key::parse(&buffer[..len]) {
+                if sender.send(Event::Key(key)).is_err() {
+                    return;
+                }
+            }
+        }
+        let _ = sender.send(Event::Closed);
+    });
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum View {
+    Trace,
+    Grid,
+}
+
+impl View {
+    fn name(self) -> &'static str {
+        match self {
+            View::Trace => "trace",
+            View::Grid => "grid",
+        }
+    }
+}
+
+/// The detail pane below the list of days.
+#[derive(Debug)]
+struct Pane {
+    view: View,
+    day: usize,
+    input: usize,
+    lines: Vec<String>,
+    scroll: usize,
+}
+
+/// A day along with the inputs it can be solved for, the first one is picked at the start.
+#[derive(Debug)]
+pub struct Day {
+    pub solver: &'static Solver,
+    pub inputs: Vec<Input>,
+}
+
+/// A part waiting to be solved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Job {
+    day: usize,
+    input: usize,
+    part: u8,
+    /// Re-runs skip the cache, they are asked for to see the solver at work.
+    fresh: bool,
+}
+
+#[derive(Debug)]
+pub struct Dashboard {
+    days: Vec<Day>,
+    options: Options,
+    selected: usize,
+    /// The input picked for every day.
+    picked: Vec<usize>,
+    /// Keyed by day, input and part, all indices into `days`.
+    outcomes: HashMap<(usize, usize, u8), Outcome>,
+    queue: VecDeque<Job>,
+    /// The part being solved on the worker thread.
+    running: Option<Job>,
+    /// Key presses and solved parts, in the order they happen.
+    events: Receiver<Event>,
+    sender: Sender<Event>,
+    pane: Option<Pane>,
+    /// Shown above the help line until the next key press.
+    message: Option<String>,
+}
+
+impl Dashboard {
+    /// Days without inputs are left out. Every day is queued to be solved for its first input.
+    pub fn new(days: Vec<Day>, options: Options) -> Self {
+        let days: Vec<_> = days
+            .into_iter()
+            .filter(|day| !day.inputs.is_empty())
+            .collect();
+        let (sender, events) = mpsc::channel();
+        let mut dashboard = Self {
+            picked: vec![0; days.len()],
+            days,
+            options,
+            selected: 0,
+            outcomes: HashMap::new(),
+            queue: VecDeque::new(),
+            running: None,
+            events,
+            sender,
+            pane: None,
+            message: None,
+        };
+        for day in 0..dashboard.days.len() {
+            dashboard.queue_unsolved(day, 0);
+        }
+        dashboard
+    }
+
+    fn queue_unsolved(&mut self, day: usize, input: usize) {
+        for part in 1..=2 {
+            let job = Job {
+                day,
+                input,
+                part,
+                fresh: false,
+            };
+            if !self.outcomes.contains_key(&(day, input, part))
+                && !self.is_queued(&job)
+                && !self.is_running(&job)
+            {
+                self.queue.push_back(job);
+            }
+        }
+    }
+
+    fn is_queued(&self, job: &Job) -> bool {
+        self.queue.iter().any(|queued| same_part(queued, job))
+    }
+
+    fn is_running(&self, job: &Job) -> bool {
+        self.running.is_some_and(|running| same_part(&running, job))
+    }
+
+    fn rerun(&mut self, part: u8) {
+        if self.days.is_empty() {
+            return;
+        }
+        let job = Job {
+            day: self.selected,
+            input: self.picked[self.selected],
+            part,
+            fresh: true,
+        };
+        self.queue.retain(|queued| !same_part(queued, &job));
+        self.queue.push_back(job);
+    }
+
+    /// Starts solving the next queued part on a worker thread, unless one is running already.
+    /// False if nothing is running.
+    fn start(&mut self) -> bool {
+        if self.running.is_some() {
+            return true;
+        }
+        let job = match self.queue.pop_front() {
+            Some(job) => job,
+            None => return false,
+        };
+        let day = &self.days[job.day];
+        let input = &day.inputs[job.input];
+        let options = Options {
+            cache: self.options.cache.clone().filter(|_| !job.fresh),
+            ..self.options.clone()
+        };
+        let solver = day.solver;
+        let (name, text) = (input.name.clone(), input.text.clone());
+        let expected = input.expected[job.part as usize - 1].clone();
+        let sender = self.sender.clone();
+        thread::spawn(move || {
+            let outcome = runner::solve_part(solver, &name, job.part, &text, expected, &options);
+            let _ = sender.send(Event::Solved(job, outcome));
+        });
+        self.running = Some(job);
+        true
+    }
+
+    fn solved(&mut self, job: Job, outcome: Outcome) {
+        self.running = None;
+        self.outcomes
+            .insert((job.day, job.input, job.part), outcome);
+    }
+
+    /// Opens the pane for the selected day and input, rendering it on a worker thread since a
+    /// large input can take as long to render as to solve.
+    fn open(&mut self, view: View) {
+        let (index, picked) = (self.selected, self.picked[self.selected]);
+        let day = &self.days[index];
+        let render = match view {
+            View::Trace => day.solver.trace,
+            View::Grid => day.solver.grid,
+        };
+        let lines = match render {
+            Some(render) => {
+                let text = day.inputs[picked].text.clone();
+                let sender = self.sender.clone();
+                thread::spawn(move || {
+                    let lines = match render(&text) {
+                        Ok(text) => text.lines().map(str::to_owned).collect(),
+                        Err(err) => vec![err.to_string()],
+                    };
+                    let _ = sender.send(Event::Rendered {
+                        view,
+                        day: index,
+                        input: picked,
+                        lines,
+                    });
+                });
+                vec![format!("rendering the {}...", view.name())]
+            }
+            None => vec![format!("day {} has no {}", day.solver.day, view.name())],
+        };
+        self.pane = Some(Pane {
+            view,
+            day: index,
+            input: picked,
+            lines,
+            scroll: 0,
+        });
+    }
+
+    /// Fills in the pane, unless something else was opened while it rendered.
+    fn rendered(&mut self, view: View, day: usize, input: usize, lines: Vec<String>) {
+        if let Some(pane) = &mut self.pane {
+            if (pane.view, pane.day, pane.input) == (view, day, input) {
+                pane.scroll = pane.scroll.min(lines.len().saturating_sub(1));
+                pane.lines = lines;
+            }
+        }
+    }
+
+    /// Shows what was selected in the open pane, if there is one.
+    fn reopen(&mut self) {
+        if let Some(view) = self.pane.as_ref().map(|pane| pane.view) {
+            self.open(view);
+        }
+    }
+
+    fn toggle(&mut self, view: View) {
+        match &self.pane {
+            Some(pane) if pane.view == view => self.pane = None,
+            _ => self.open(view),
+        }
+    }
+
+    fn scroll(&mut self, by: isize) {
+        if let Some(pane) = &mut self.pane {
+            let last = pane.lines.len().saturating_sub(1);
+            pane.scroll = pane.scroll.saturating_add_signed(by).min(last);
+        }
+    }
+
+    /// Acts on a key press, false once the dashboard should close.
+    fn handle(&mut self, key: Key) -> bool {
+        self.message = None;
+        if self.days.is_empty() {
+            return !matches!(key, Key::Char('q' | '\x1b'));
+        }
+        let inputs = self.days[self.selected].inputs.len();
+        match key {
+            Key::Up | Key::Char('k') => {
+                self.selected = self.selected.saturating_sub(1);
+                self.reopen();
+            }
+            Key::Down | Key::Char('j') => {
+                self.selected = (self.selected + 1).min(self.days.len() - 1);
+                self.reopen();
+            }
+            Key::Left | Key::Right | Key::Char('h' | 'l') => {
+                let picked = &mut self.picked[self.selected];
+                *picked = match key {
+                    Key::Left | Key::Char('h') => (*picked + inputs - 1) % inputs,
+                    _ => (*picked + 1) % inputs,
+                };
+                self.queue_unsolved(self.selected, self.picked[self.selected]);
+                self.reopen();
+            }
+            Key::Char(part @ ('1' | '2')) => self.rerun(part as u8 - b'0'),
+            Key::Char('r') => {
+                self.rerun(1);
+                self.rerun(2);
+            }
+            Key::Char('t') => self.toggle(View::Trace),
+            Key::Char('g') => self.toggle(View::Grid),
+            Key::PageUp => self.scroll(-(PAGE as isize)),
+            Key::PageDown => self.scroll(PAGE as isize),
+            Key::Char('\x1b') if self.pane.is_some() => self.pane = None,
+            Key::Char('q' | '\x1b') => return false,
+            Key::Char(c) => self.message = Some(format!("{:?} does nothing, see below", c)),
+        }
+        true
+    }
+
+    /// The cells of a part: answer, status and time.
+    fn part_cells(&self, day: usize, part: u8) -> [(&'static str, String); 3] {
+        let input = self.picked[day];
+        let job = Job {
+            day,
+            input,
+            part,
+            fresh: false,
+        };
+        let outcome = self.outcomes.get(&(day, input, part));
+        let status = match (self.is_running(&job), self.is_queued(&job), outcome) {
+            (true, _, _) => (DIM, "running".to_owned()),
+            (_, true, _) => (DIM, "queued".to_owned()),
+            (_, _, Some(outcome)) => {
+                let color = match outcome.status() {
+                    Status::Correct => GREEN,
+                    Status::Wrong | Status::Failed => RED,
+                    Status::Unverified => "",
+                };
+                (color, outcome.status().name().to_owned())
+            }
+            (_, _, None) => (DIM, "-".to_owned()),
+        };
+        let (answer, time) = match outcome {
+            Some(outcome) => (
+                outcome.answer.clone().unwrap_or_else(|_| "-".to_owned()),
+                if outcome.cached {
+                    "cached".to_owned()
+                } else {
+                    format!("{:.2?}", outcome.elapsed)
+                },
+            ),
+            None => ("-".to_owned(), "-".to_owned()),
+        };
+        [("", answer), status, ("", time)]
+    }
+
+    fn rows(&self) -> Vec<[(&'static str, String); 8]> {
+        let header = [
+            "day", "input", "part 1", "status", "time", "part 2", "status", "time",
+        ]
+        .map(|title| (BOLD, title.to_owned()));
+        let days = self.days.iter().enumerate().map(|(index, day)| {
+            let picked = self.picked[index];
+            let [answer1, status1, time1] = self.part_cells(index, 1);
+            let [answer2, status2, time2] = self.part_cells(index, 2);
+            [
+                ("", format!("{:>3}", day.solver.day)),
+                (
+                    "",
+                    format!(
+                        "{} ({}/{})",
+                        day.inputs[picked].name,
+                        picked + 1,
+                        day.inputs.len()
+                    ),
+                ),
+                answer1,
+                status1,
+                time1,
+                answer2,
+                status2,
+                time2,
+            ]
+        });
+        std::iter::once(header).chain(days).collect()
+    }
+
+    /// What the selected day's outcomes have to say beyond their status, such as errors.
+    fn details(&self) -> Option<String> {
+        let input = *self.picked.get(self.selected)?;
+        let details: Vec<_> = (1..=2)
+            .filter_map(|part| {
+                let outcome = self.outcomes.get(&(self.selected, input, part))?;
+                outcome
+                    .is_flagged()
+                    .then(|| format!("part {}: {}", part, outcome.describe_status()))
+            })
+            .collect();
+        (!details.is_empty()).then(|| details.join("  "))
+    }
+
+    /// The screen as lines of exactly `width` visible characters, `height` of them.
+    fn lines(&self, width: usize, height: usize) -> Vec<String> {
+        let mut lines = vec![fit(
+            &[(BOLD, "Advent of Code dashboard".to_owned())],
+            width,
+            "",
+        )];
+        let rows = self.rows();
+        let mut widths = [0; 8];
+        for row in &rows {
+            for (width, (_, cell)) in widths.iter_mut().zip(row) {
+                *width = (*width).max(cell.chars().count());
+            }
+        }
+        for (index, row) in rows.iter().enumerate() {
+            let selected = index == self.selected + 1;
+            let cells: Vec<_> = row
+                .iter()
+                .zip(widths)
+                .enumerate()
+                .map(|(column, ((style, cell), width))| {
+                    let gap = if column == 0 { "" } else { "  " };
+                    (*style, format!("{}{:<width$}", gap, cell, width = width))
+                })
+                .collect();
+            let marker = if selected { ">" } else { " " };
+            let cells: Vec<_> = std::iter::once(("", marker.to_owned()))
+                .chain(cells)
+                .collect();
+            lines.push(fit(&cells, width, if selected { REVERSE } else { "" }));
+        }
+        let footer = [
+            fit(
+                &[(
+                    "",
+                    self.message
+                        .clone()
+                        .or_else(|| self.details())
+                        .unwrap_or_default(),
+                )],
+                width,
+                "",
+            ),
+            fit(&[(DIM, HELP.to_owned())], width, ""),
+        ];
+        if let Some(pane) = &self.pane {
+            let day = &self.days[self.selected];
+            let title = format!(
+                "day {} {} of {}",
+                day.solver.day,
+                pane.view.name(),
+                day.inputs[self.picked[self.selected]].name
+            );
+            lines.push(fit(&[(REVERSE, title)], width, REVERSE));
+            let room = height.saturating_sub(lines.len() + footer.len());
+            for line in pane.lines.iter().skip(pane.scroll).take(room) {
+                lines.push(fit(&[("", line.clone())], width, ""));
+            }
+        }
+        while lines.len() + footer.len() < height {
+            lines.push(fit(&[], width, ""));
+        }
+        lines.truncate(height.saturating_sub(footer.len()));
+        lines.extend(footer);
+        lines.truncate(height);
+        lines
+    }
+
+    fn draw(&self, out: &mut impl Write) -> io::Result<()> {
+        let (width, height) = terminal::size().unwrap_or(DEFAULT_SIZE);
+        write!(
+            out,
+            "{}{}",
+            CURSOR_HOME,
+            self.lines(width, height).join("\r\n")
+        )?;
+        out.flush()
+    }
+
+    /// Runs until `q` is pressed. A part still being solved then is left to finish in the
+    /// background, it ends with the process.
+    pub fn run(mut self) -> io::Result<()> {
+        let _raw_mode = RawMode::enable();
+        spawn_key_reader(self.sender.clone());
+        let mut out = io::stdout();
+        write!(out, "{}{}", ENTER_ALTERNATE_SCREEN, HIDE_CURSOR)?;
+        let mut keyboard = true;
+        let result = loop {
+            let running = self.start();
+            if let Err(err) = self.draw(&mut out) {
+                break Err(err);
+            }
+            // without a keyboard there is nothing left to do once the queue is done
+            if !keyboard && !running {
+                break Ok(());
+            }
+            match self.events.recv().expect("the dashboard holds a sender") {
+                Event::Key(key) => {
+                    if !self.handle(key) {
+                        break Ok(());
+                    }
+                }
+                Event::Solved(job, outcome) => self.solved(job, outcome),
+                Event::Rendered {
+                    view,
+                    day,
+                    input,
+                    lines,
+                } => self.rendered(view, day, input, lines),
+                Event::Closed => keyboard = false,
+            }
+        };
+        write!(out, "{}{}", SHOW_CURSOR, LEAVE_ALTERNATE_SCREEN)?;
+        out.flush()?;
+        result
+    }
+}
+
+/// Whether two jobs solve the same part of the same input, fresh or not.
+fn same_part(a: &Job, b: &Job) -> bool {
+    (a.day, a.input, a.part) == (b.day, b.input, b.part)
+}
+
+/// Joins styled cells into a line cut or padded to `width` visible characters, with `base`
+/// applied to all of it.
+fn fit(cells: &[(&str, String)], width: usize, base: &str) -> String {
+    let mut line = String::new();
+    let mut left = width;
+    for (style, text) in cells {
+        let text: String = text.chars().take(left).collect();
+        left -= text.chars().count();
+        line.push_str(&format!("{}{}{}{}", base, style, text, RESET));
+    }
+    line.push_str(&format!("{}{}{}", base, " ".repeat(left), RESET));
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solvers;
+
+    /// The line without its escape codes and trailing padding.
+    fn plain(line: &str) -> String {
+        let mut plain = String::new();
+        let mut chars = line.chars();
+        while let Some(c) = chars.next() {
+            if c == '\x1b' {
+                chars.by_ref().find(|c| c.is_ascii_alphabetic());
+            } else {
+                plain.push(c);
+            }
+        }
+        plain.trim_end().to_owned()
+    }
+
+    fn input(name: &str, text: &str, expected: [Option<&str>; 2]) -> Input {
+        Input {
+            name: name.to_owned(),
+            text: text.to_owned(),
+            expected: expected.map(|answer| answer.map(str::to_owned)),
+        }
+    }
+
+    fn dashboard() -> Dashboard {
+        let days = vec![
+            Day {
+                solver: solvers::solver(1).unwrap(),
+                inputs: vec![
+                    input("small", "1\n2\n1\n3", [Some("2"), Some("5")]),
+                    input("other", "1\n2", [None, None]),
+                ],
+            },
+            Day {
+                solver: solvers::solver(9).unwrap(),
+                inputs: vec![input("tiny", "2199\n3987", [None, None])],
+            },
+            Day {
+                solver: solvers::solver(2).unwrap(),
+                inputs: Vec::new(),
+            },
+        ];
+        Dashboard::new(days, Options::default())
+    }
+
+    /// Solves everything queued, waiting for the worker thread.
+    fn solve_all(dashboard: &mut Dashboard) {
+        while dashboard.start() {
+            match dashboard.events.recv().unwrap() {
+                Event::Solved(job, outcome) => dashboard.solved(job, outcome),
+                event => panic!("unexpected {:?}", event),
+            }
+        }
+    }
+
+    /// Waits for the open pane to be rendered.
+    fn render(dashboard: &mut Dashboard) {
+        match dashboard.events.recv().unwrap() {
+            Event::Rendered {
+                view,
+                day,
+                input,
+                lines,
+            } => dashboard.rendered(view, day, input, lines),
+            event => panic!("unexpected {:?}", event),
+        }
+    }
+
+    #[test]
+    fn test_keys() {
+        assert_eq!(
+            Key::parse(b"q\x1b[A\x1b[6~\x1b[1;5Cx\x1b"),
+            [
+                Key::Char('q'),
+                Key::Up,
+                Key::PageDown,
+                Key::Char('x'),
+                Key::Char('\x1b')
+            ]
+        );
+    }
+
+    #[test]
+    fn test_solve() {
+        let mut dashboard = dashboard();
+        assert!(plain(&dashboard.lines(80, 5)[2]).contains("queued"));
+        dashboard.start();
+        assert_eq!(
+            plain(&dashboard.lines(80, 5)[2]),
+            ">  1  small (1/2)  -       running  -     -       queued  -"
+        );
+        solve_all(&mut dashboard);
+        let lines: Vec<_> = dashboard
+            .lines(100, 6)
+            .iter()
+            .map(|line| plain(line))
+            .collect();
+        assert_eq!(
+            lines[1],
+            " day  input        part 1  status      time     part 2  status      time"
+        );
+        assert!(lines[2].starts_with(">  1  small (1/2)  2       correct     "));
+        assert!(lines[2].contains("  1       wrong       "));
+        assert!(lines[3].starts_with("   9  tiny (1/1)   10      unverified  "));
+        assert_eq!(lines[4], "part 2: WRONG, expected 5");
+        assert!(lines[5].starts_with("up/down day"));
+        assert!(dashboard
+            .lines(40, 6)
+            .iter()
+            .all(|line| plain(line).chars().count() <= 40));
+    }
+
+    #[test]
+    fn test_handle() {
+        let mut dashboard = dashboard();
+        solve_all(&mut dashboard);
+        dashboard.handle(Key::Right);
+        assert_eq!(dashboard.queue.len(), 2);
+        solve_all(&mut dashboard);
+        assert!(plain(&dashboard.lines(80, 5)[2]).starts_with(">  1  other (2/2)  1"));
+        dashboard.handle(Key::Char('2'));
+        assert_eq!(
+            dashboard.queue,
+            [Job {
+                day: 0,
+                input: 1,
+                part: 2,
+                fresh: true
+            }]
+        );
+        dashboard.handle(Key::Down);
+        dashboard.handle(Key::Down);
+        assert_eq!(dashboard.selected, 1);
+        assert!(dashboard.handle(Key::Char('x')));
+        assert!(dashboard.message.is_some());
+        assert!(!dashboard.handle(Key::Char('q')));
+    }
+
+    #[test]
+    fn test_pane() {
+        let mut dashboard = dashboard();
+        dashboard.handle(Key::Char('g'));
+        let lines: Vec<_> = dashboard
+            .lines(60, 10)
+            .iter()
+            .map(|line| plain(line))
+            .collect();
+        assert_eq!(lines[4], "day 1 grid of small");
+        assert_eq!(lines[5], "day 1 has no grid");
+        dashboard.handle(Key::Down);
+        assert_eq!(plain(&dashboard.lines(60, 10)[5]), "rendering the grid...");
+        render(&mut dashboard);
+        let lines: Vec<_> = dashboard
+            .lines(60, 10)
+            .iter()
+            .map(|line| plain(line))
+            .collect();
+        assert_eq!(&lines[4..7], ["day 9 grid of tiny", ".o##", ".#.o"]);
+        // a render that finishes after the pane moved on is dropped
+        dashboard.handle(Key::Char('t'));
+        dashboard.handle(Key::Char('g'));
+        render(&mut dashboard);
+        render(&mut dashboard);
+        assert_eq!(
+            dashboard.pane.as_ref().unwrap().lines[..2],
+            [".o##", ".#.o"]
+        );
+        dashboard.handle(Key::Char('t'));
+        render(&mut dashboard);
+        dashboard.handle(Key::PageDown);
+        let lines: Vec<_> = dashboard
+            .lines(60, 10)
+            .iter()
+            .map(|line| plain(line))
+            .collect();
+        assert_eq!(
+            &lines[4..6],
+            [
+                "day 9 trace of tiny",
+                "low point ( 3,   1) height 7: basin size 2"
+            ]
+        );
+        dashboard.handle(Key::Char('t'));
+        assert!(dashboard.pane.is_none());
+    }
+}
@@ -1,20 +1,174 @@
+use crate::solvers::SolveError;
+use crate::utils::cancel::CancelToken;
+use crate::utils::parse::{self, integer, ParseError, Separator, Span};
 use crate::utils::parse_lines;
+use crate::utils::stream::LineSource;
 use itertools::Itertools;
+use std::collections::VecDeque;
+use std::io::BufRead;
 
-fn count_increases(input: &str) -> usize {
-    parse_lines::<i32>(input)
+pub mod analysis;
+pub mod filters;
+
+/// The sums of every window of `size` consecutive depths. The sum is rolled along by adding the
+/// depth entering the window and subtracting the one leaving it, so only one window is kept in
+/// memory.
+#[derive(Debug, Clone)]
+pub struct WindowSums<I> {
+    depths: I,
+    window: VecDeque<i32>,
+    size: usize,
+    sum: i64,
+}
+
+impl<I: Iterator<Item = i32>> Iterator for WindowSums<I> {
+    type Item = i64;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let depth = self.depths.next()?;
+            self.window.push_back(depth);
+            self.sum += depth as i64;
+            if self.window.len() > self.size {
+                self.sum -= self.window.pop_front().unwrap() as i64;
+            }
+            if self.window.len() == self.size {
+                return Some(self.sum);
+            }
+        }
+    }
+}
+
+/// Panics if `size` is zero.
+pub fn window_sums<I: IntoIterator<Item = i32>>(depths: I, size: usize) -> WindowSums<I::IntoIter> {
+    assert!(size > 0, "windows need at least one depth");
+    WindowSums {
+        depths: depths.into_iter(),
+        window: VecDeque::with_capacity(size + 1),
+        size,
+        sum: 0,
+    }
+}
+
+/// Counts how often the sum of a sliding window of `size` depths increases.
+pub fn count_window_increases(depths: impl IntoIterator<Item = i32>, size: usize) -> usize {
+    window_sums(depths, size)
         .tuple_windows()
-        .filter(|(a, b)| b > a)
+        .filter(|(previous, sum)| sum > previous)
         .count()
 }
 
+/// Counts window increases for readings that arrive one at a time, for several window sizes at
+/// once. The sums of two neighbouring windows share all but their first and last depth, so a
+/// sum increases exactly when the new depth is deeper than the one `size` readings back. Only
+/// the last readings of the widest window are kept.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SonarCounter {
+    /// The last `widest` readings, the newest last.
+    recent: VecDeque<i32>,
+    widest: usize,
+    /// Each window size with how often its sum increased so far.
+    counts: Vec<(usize, usize)>,
+    readings: usize,
+}
+
+impl SonarCounter {
+    /// Panics if any size is zero.
+    pub fn new(sizes: &[usize]) -> Self {
+        assert!(
+            sizes.iter().all(|&size| size > 0),
+            "windows need at least one depth"
+        );
+        let widest = sizes.iter().copied().max().unwrap_or(0);
+        Self {
+            recent: VecDeque::with_capacity(widest + 1),
+            widest,
+            counts: sizes.iter().map(|&size| (size, 0)).collect(),
+            readings: 0,
+        }
+    }
+
+    pub fn push(&mut self, depth: i32) {
+        for (size, count) in &mut self.counts {
+            let leaving = self
+                .recent
+                .len()
+                .checked_sub(*size)
+                .map(|index| self.recent[index]);
+            if leaving.is_some_and(|leaving| depth > leaving) {
+                *count += 1;
+            }
+        }
+        self.recent.push_back(depth);
+        if self.recent.len() > self.widest {
+            self.recent.pop_front();
+        }
+        self.readings += 1;
+    }
+
+    /// How often the sums of windows of `size` depths increased so far, None if that size was
+    /// not configured.
+    pub fn count(&self, size: usize) -> Option<usize> {
+        self.counts
+            .iter()
+            .find(|&&(configured, _)| configured == size)
+            .map(|&(_, count)| count)
+    }
+
+    /// Each configured window size with its count, in the order they were configured.
+    pub fn counts(&self) -> &[(usize, usize)] {
+        &self.counts
+    }
+
+    pub fn readings(&self) -> usize {
+        self.readings
+    }
+}
+
+fn count_increases(input: &str) -> usize {
+    count_window_increases(parse_lines::<i32>(input), 1)
+}
+
 fn count_triple_increases(input: &str) -> usize {
-    parse_lines::<i32>(input)
-        .tuple_windows()
-        .map(|(a, b, c)| a + b + c)
-        .tuple_windows()
-        .filter(|(a, b)| b > a)
-        .count()
+    count_window_increases(parse_lines::<i32>(input), 3)
+}
+
+fn stream_window_increases(
+    reader: &mut dyn BufRead,
+    cancel: &CancelToken,
+    size: usize,
+) -> Result<String, SolveError> {
+    let mut counter = SonarCounter::new(&[size]);
+    for depth in LineSource::new(reader, cancel).parse(integer::<i32>) {
+        counter.push(depth?);
+    }
+    Ok(counter.counts()[0].1.to_string())
+}
+
+pub fn parse(input: &str) -> Result<Vec<i32>, SolveError> {
+    Ok(parse::separated(Separator::Lines, integer)(Span::new(
+        input,
+    ))?)
+}
+
+pub fn lint(input: &str) -> Vec<ParseError> {
+    parse::lint(Span::new(input), Separator::Lines, integer::<i32>)
+}
+
+pub fn part1(input: &str, _cancel: &CancelToken) -> Result<String, SolveError> {
+    Ok(count_increases(input).to_string())
+}
+
+pub fn part2(input: &str, _cancel: &CancelToken) -> Result<String, SolveError> {
+    Ok(count_triple_increases(input).to_string())
+}
+
+pub fn stream_part1(reader: &mut dyn BufRead, cancel: &CancelToken) -> Result<String, SolveError> {
+    stream_window_increases(reader, cancel, 1)
+}
+
+pub fn stream_part2(reader: &mut dyn BufRead, cancel: &CancelToken) -> Result<String, SolveError> {
+    stream_window_increases(reader, cancel, 3)
 }
 
 pub fn run() {
@@ -26,8 +180,9 @@ pub fn run() {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use rstest::*;
 
-    static TEST_INPUT: &'static str = "199
+    static TEST_INPUT: &str = "199
 200
 208
 210
@@ -47,4 +202,66 @@ mod tests {
     fn test_count_triple_increases() {
         assert_eq!(count_triple_increases(TEST_INPUT), 5);
     }
+
+    #[rstest]
+    #[case::single(1, 7)]
+    #[case::pairs(2, 5)]
+    #[case::triples(3, 5)]
+    #[case::whole_input(10, 0)]
+    #[case::longer_than_input(11, 0)]
+    fn test_count_window_increases(#[case] size: usize, #[case] increases: usize) {
+        let depths = parse(TEST_INPUT).unwrap();
+        assert_eq!(count_window_increases(depths, size), increases);
+    }
+
+    #[test]
+    fn test_window_sums() {
+        let depths = parse(TEST_INPUT).unwrap();
+        assert_eq!(
+            window_sums(depths, 3).collect::<Vec<_>>(),
+            [607, 618, 618, 617, 647, 716, 769, 792]
+        );
+        assert_eq!(
+            window_sums([i32::MAX, i32::MAX], 2).collect::<Vec<_>>(),
+            [2 * i32::MAX as i64]
+        );
+    }
+
+    #[test]
+    fn test_sonar_counter() {
+        let mut counter = SonarCounter::new(&[1, 3, 11]);
+        for depth in parse(TEST_INPUT).unwrap() {
+            counter.push(depth);
+        }
+        assert_eq!(counter.counts(), [(1, 7), (3, 5), (11, 0)]);
+        assert_eq!((counter.count(3), counter.count(2)), (Some(5), None));
+        assert_eq!(counter.readings(), 10);
+        assert_eq!(counter.recent.len(), 10);
+        let mut counter = SonarCounter::new(&[2]);
+        for depth in [5, 1, 6, 2, 2] {
+            counter.push(depth);
+        }
+        assert_eq!((counter.count(2), counter.recent.len()), (Some(2), 2));
+    }
+
+    #[test]
+    fn test_stream() {
+        let cancel = CancelToken::new();
+        let mut reader = TEST_INPUT.as_bytes();
+        assert_eq!(stream_part1(&mut reader, &cancel), Ok("7".to_owned()));
+        let mut reader = TEST_INPUT.as_bytes();
+        assert_eq!(stream_part2(&mut reader, &cancel), Ok("5".to_owned()));
+        let mut reader = "1\n2\nx".as_bytes();
+        assert!(matches!(
+            stream_part1(&mut reader, &cancel),
+            Err(SolveError::Parse(errors)) if errors[0].line == 3
+        ));
+    }
+
+    #[test]
+    fn test_lint() {
+        assert!(lint(TEST_INPUT).is_empty());
+        let errors = lint("199\n2OO\n208");
+        assert_eq!((errors.len(), errors[0].line), (1, 2));
+    }
 }
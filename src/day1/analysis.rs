@@ -0,0 +1,332 @@
+//! Looks at the shape of a sonar sweep rather than only counting increases: the longest runs of
+//! strictly increasing and decreasing depths, the regions the sweep trends up, down or stays
+//! flat in, and sudden jumps in depth.
+
+use super::window_sums;
+use crate::utils::parse_lines;
+use itertools::Itertools;
+use std::fmt::Write;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Trend {
+    Increasing,
+    Decreasing,
+    Flat,
+}
+
+impl Trend {
+    fn of(change: i64) -> Self {
+        match change {
+            0 => Trend::Flat,
+            change if change > 0 => Trend::Increasing,
+            _ => Trend::Decreasing,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Trend::Increasing => "increasing",
+            Trend::Decreasing => "decreasing",
+            Trend::Flat => "flat",
+        }
+    }
+}
+
+/// A stretch of the sweep, `start` and `end` are indices of its first and last depth.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Stretch {
+    pub start: usize,
+    pub end: usize,
+    pub trend: Trend,
+    /// The depth at the end minus the depth at the start, for segments of the window sums.
+    pub change: i64,
+}
+
+impl Stretch {
+    /// The number of depths in the stretch.
+    pub fn len(&self) -> usize {
+        self.end - self.start + 1
+    }
+}
+
+/// When a jump from one depth to the next counts as an anomaly.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Detector {
+    /// The jump is larger than this many units either way.
+    Jump(u32),
+    /// The jump is further than this many standard deviations from the mean jump.
+    ZScore(f64),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Anomaly {
+    /// The index of the depth that was jumped to.
+    pub index: usize,
+    pub jump: i64,
+    pub z_score: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Options {
+    /// Trends are found in the sums of windows this many depths wide, wider windows smooth out
+    /// noise. Panics if zero.
+    pub window: usize,
+    pub detector: Detector,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            window: 1,
+            detector: Detector::ZScore(3.0),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Analysis {
+    pub longest_increase: Option<Stretch>,
+    pub longest_decrease: Option<Stretch>,
+    /// The sweep split where the trend of the window sums changes. Neighbouring segments share
+    /// their boundary, the indices are of the first depth of each window.
+    pub segments: Vec<Stretch>,
+    pub anomalies: Vec<Anomaly>,
+}
+
+/// The longest run of depths that strictly follow the trend, the first one on ties. None if
+/// not even two depths do.
+fn longest_run(depths: &[i32], trend: Trend) -> Option<Stretch> {
+    let mut longest: Option<Stretch> = None;
+    let mut start = 0;
+    for end in 1..=depths.len() {
+        let continues =
+            end < depths.len() && Trend::of(depths[end] as i64 - depths[end - 1] as i64) == trend;
+        if continues {
+            continue;
+        }
+        let run = Stretch {
+            start,
+            end: end - 1,
+            trend,
+            change: depths[end - 1] as i64 - depths[start] as i64,
+        };
+        if run.len() >= 2 && longest.is_none_or(|longest| run.len() > longest.len()) {
+            longest = Some(run);
+        }
+        start = end;
+    }
+    longest
+}
+
+fn segments(series: &[i64]) -> Vec<Stretch> {
+    let mut segments: Vec<Stretch> = Vec::new();
+    for (index, (previous, value)) in series.iter().tuple_windows().enumerate() {
+        let trend = Trend::of(value - previous);
+        match segments.last_mut() {
+            Some(segment) if segment.trend == trend => {
+                segment.end = index + 1;
+                segment.change += value - previous;
+            }
+            _ => segments.push(Stretch {
+                start: index,
+                end: index + 1,
+                trend,
+                change: value - previous,
+            }),
+        }
+    }
+    segments
+}
+
+fn anomalies(depths: &[i32], detector: Detector) -> Vec<Anomaly> {
+    let jumps = depths
+        .iter()
+        .tuple_windows()
+        .map(|(&previous, &depth)| depth as i64 - previous as i64)
+        .collect_vec();
+    let count = jumps.len().max(1) as f64;
+    let mean = jumps.iter().sum::<i64>() as f64 / count;
+    let deviation = (jumps
+        .iter()
+        .map(|&jump| (jump as f64 - mean).powi(2))
+        .sum::<f64>()
+        / count)
+        .sqrt();
+    jumps
+        .iter()
+        .enumerate()
+        .map(|(index, &jump)| Anomaly {
+            index: index + 1,
+            jump,
+            z_score: if deviation > 0.0 {
+                (jump as f64 - mean) / deviation
+            } else {
+                0.0
+            },
+        })
+        .filter(|anomaly| match detector {
+            Detector::Jump(threshold) => anomaly.jump.unsigned_abs() > threshold as u64,
+            Detector::ZScore(limit) => anomaly.z_score.abs() > limit,
+        })
+        .collect()
+}
+
+impl Analysis {
+    pub fn new(depths: &[i32], options: &Options) -> Self {
+        let sums = window_sums(depths.iter().copied(), options.window).collect_vec();
+        Self {
+            longest_increase: longest_run(depths, Trend::Increasing),
+            longest_decrease: longest_run(depths, Trend::Decreasing),
+            segments: segments(&sums),
+            anomalies: anomalies(depths, options.detector),
+        }
+    }
+
+    /// Analyses the depths of an input, lines that are not a depth are skipped.
+    pub fn from_input(input: &str, options: &Options) -> Self {
+        Self::new(&parse_lines::<i32>(input).collect_vec(), options)
+    }
+
+    /// One row per finding, each as the stretch of the sweep it covers. Anomalies cover the
+    /// jump into the anomalous depth.
+    pub fn to_csv(&self) -> String {
+        let mut csv = "kind,start,end,trend,change,z_score\n".to_owned();
+        let mut row = |kind: &str, stretch: &Stretch, z_score: Option<f64>| {
+            let _ = writeln!(
+                csv,
+                "{},{},{},{},{},{}",
+                kind,
+                stretch.start,
+                stretch.end,
+                stretch.trend.name(),
+                stretch.change,
+                z_score.map_or(String::new(), |z_score| format!("{:.3}", z_score))
+            );
+        };
+        for (kind, run) in [
+            ("longest_increase", &self.longest_increase),
+            ("longest_decrease", &self.longest_decrease),
+        ] {
+            if let Some(run) = run {
+                row(kind, run, None);
+            }
+        }
+        for segment in &self.segments {
+            row("segment", segment, None);
+        }
+        for anomaly in &self.anomalies {
+            let stretch = Stretch {
+                start: anomaly.index - 1,
+                end: anomaly.index,
+                trend: Trend::of(anomaly.jump),
+                change: anomaly.jump,
+            };
+            row("anomaly", &stretch, Some(anomaly.z_score));
+        }
+        csv
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static TEST_INPUT: &str = "199\n200\n208\n210\n200\n207\n240\n269\n260\n263";
+
+    fn stretch(start: usize, end: usize, trend: Trend, change: i64) -> Stretch {
+        Stretch {
+            start,
+            end,
+            trend,
+            change,
+        }
+    }
+
+    #[test]
+    fn test_runs() {
+        let analysis = Analysis::from_input(TEST_INPUT, &Options::default());
+        assert_eq!(
+            analysis.longest_increase,
+            Some(stretch(0, 3, Trend::Increasing, 11))
+        );
+        assert_eq!(
+            analysis.longest_decrease,
+            Some(stretch(3, 4, Trend::Decreasing, -10))
+        );
+        assert_eq!(longest_run(&[3, 3, 3], Trend::Increasing), None);
+        assert_eq!(longest_run(&[], Trend::Decreasing), None);
+    }
+
+    #[test]
+    fn test_segments() {
+        let analysis = Analysis::from_input("1\n2\n3\n3\n2\n5", &Options::default());
+        assert_eq!(
+            analysis.segments,
+            [
+                stretch(0, 2, Trend::Increasing, 2),
+                stretch(2, 3, Trend::Flat, 0),
+                stretch(3, 4, Trend::Decreasing, -1),
+                stretch(4, 5, Trend::Increasing, 3),
+            ]
+        );
+        let options = Options {
+            window: 3,
+            ..Options::default()
+        };
+        let analysis = Analysis::from_input(TEST_INPUT, &options);
+        assert_eq!(
+            analysis.segments,
+            [
+                stretch(0, 1, Trend::Increasing, 11),
+                stretch(1, 2, Trend::Flat, 0),
+                stretch(2, 3, Trend::Decreasing, -1),
+                stretch(3, 7, Trend::Increasing, 175),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_anomalies() {
+        let input = "10\n11\n12\n11\n40\n41\n40\n39\n40\n41\n42";
+        let options = Options {
+            detector: Detector::Jump(5),
+            ..Options::default()
+        };
+        let anomalies = Analysis::from_input(input, &options).anomalies;
+        assert_eq!(
+            anomalies
+                .iter()
+                .map(|anomaly| (anomaly.index, anomaly.jump))
+                .collect_vec(),
+            [(4, 29)]
+        );
+        let options = Options {
+            detector: Detector::ZScore(2.0),
+            ..Options::default()
+        };
+        let anomalies = Analysis::from_input(input, &options).anomalies;
+        assert_eq!(anomalies.len(), 1);
+        assert!(anomalies[0].z_score > 2.9);
+        assert!(Analysis::from_input("5\n5\n5", &options)
+            .anomalies
+            .is_empty());
+    }
+
+    #[test]
+    fn test_csv() {
+        let options = Options {
+            detector: Detector::Jump(20),
+            ..Options::default()
+        };
+        assert_eq!(
+            Analysis::from_input("1\n2\n30\n29", &options).to_csv(),
+            "kind,start,end,trend,change,z_score
+longest_increase,0,2,increasing,29,
+longest_decrease,2,3,decreasing,-1,
+segment,0,2,increasing,29,
+segment,2,3,decreasing,-1,
+anomaly,1,2,increasing,28,1.412
+"
+        );
+    }
+}
@@ -0,0 +1,238 @@
+//! Smoothing for noisy sweeps, so jitter is not counted as the floor getting deeper. Filters
+//! take one depth at a time and keep only what their window needs, so they work on a stream.
+
+use crate::solvers::SolveError;
+use crate::utils::cancel::CancelToken;
+use crate::utils::parse::integer;
+use crate::utils::stream::LineSource;
+use std::collections::VecDeque;
+use std::fmt;
+use std::io::BufRead;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Filter {
+    /// The mean of the last this many depths.
+    MovingAverage(usize),
+    /// Weighs the latest depth by this factor, between 0 and 1, and the previous average by the
+    /// rest, so older depths fade out.
+    ExponentialAverage(f64),
+    /// The median of the last this many depths, which ignores single spikes entirely.
+    Median(usize),
+    /// Holds on to a depth until one differs from it by more than this much.
+    Hysteresis(f64),
+}
+
+impl fmt::Display for Filter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Filter::MovingAverage(size) => write!(f, "sma:{}", size),
+            Filter::ExponentialAverage(alpha) => write!(f, "ema:{}", alpha),
+            Filter::Median(size) => write!(f, "median:{}", size),
+            Filter::Hysteresis(delta) => write!(f, "hysteresis:{}", delta),
+        }
+    }
+}
+
+/// Parses the form filters are displayed in, such as `sma:3` or `hysteresis:2.5`.
+impl FromStr for Filter {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, parameter) = s
+            .split_once(':')
+            .ok_or_else(|| format!("expected a filter like \"sma:3\", found {:?}", s))?;
+        let size = || match parameter.parse() {
+            Ok(size @ 1..) => Ok(size),
+            _ => Err(format!("expected a window size, found {:?}", parameter)),
+        };
+        let factor = |valid: fn(f64) -> bool, what: &str| match parameter.parse() {
+            Ok(factor) if valid(factor) => Ok(factor),
+            _ => Err(format!("expected {}, found {:?}", what, parameter)),
+        };
+        match name {
+            "sma" => Ok(Filter::MovingAverage(size()?)),
+            "ema" => Ok(Filter::ExponentialAverage(factor(
+                |alpha| alpha > 0.0 && alpha <= 1.0,
+                "a factor between 0 and 1",
+            )?)),
+            "median" => Ok(Filter::Median(size()?)),
+            "hysteresis" => Ok(Filter::Hysteresis(factor(
+                |delta| delta >= 0.0,
+                "a change of at least 0",
+            )?)),
+            _ => Err(format!(
+                "expected sma, ema, median or hysteresis, found {:?}",
+                name
+            )),
+        }
+    }
+}
+
+impl Filter {
+    pub fn start(self) -> Smoother {
+        Smoother {
+            filter: self,
+            window: VecDeque::new(),
+            value: None,
+        }
+    }
+}
+
+/// A filter applied to the depths seen so far.
+#[derive(Debug, Clone)]
+pub struct Smoother {
+    filter: Filter,
+    /// The last depths, for the filters that work on a window.
+    window: VecDeque<i32>,
+    /// The last output, for the filters that depend on it.
+    value: Option<f64>,
+}
+
+impl Smoother {
+    /// Takes the next depth and returns the smoothed one, None while a window is filling up.
+    pub fn push(&mut self, depth: i32) -> Option<f64> {
+        let depth_value = depth as f64;
+        match self.filter {
+            Filter::MovingAverage(size) | Filter::Median(size) => {
+                self.window.push_back(depth);
+                if self.window.len() > size {
+                    self.window.pop_front();
+                }
+                if self.window.len() < size {
+                    return None;
+                }
+                if let Filter::MovingAverage(_) = self.filter {
+                    let sum: i64 = self.window.iter().map(|&depth| depth as i64).sum();
+                    return Some(sum as f64 / size as f64);
+                }
+                let mut sorted: Vec<_> = self.window.iter().copied().collect();
+                sorted.sort_unstable();
+                let middle = size / 2;
+                Some(if size % 2 == 1 {
+                    sorted[middle] as f64
+                } else {
+                    (sorted[middle - 1] as f64 + sorted[middle] as f64) / 2.0
+                })
+            }
+            Filter::ExponentialAverage(alpha) => {
+                let value = self.value.map_or(depth_value, |value| {
+                    alpha * depth_value + (1.0 - alpha) * value
+                });
+                self.value = Some(value);
+                Some(value)
+            }
+            Filter::Hysteresis(delta) => {
+                let value = match self.value {
+                    Some(value) if (depth_value - value).abs() <= delta => value,
+                    _ => depth_value,
+                };
+                self.value = Some(value);
+                Some(value)
+            }
+        }
+    }
+}
+
+/// The smoothed depths, lazily.
+pub fn smooth(depths: impl IntoIterator<Item = i32>, filter: Filter) -> impl Iterator<Item = f64> {
+    let mut smoother = filter.start();
+    depths
+        .into_iter()
+        .filter_map(move |depth| smoother.push(depth))
+}
+
+/// Counts how often the sum of a sliding window of `size` smoothed depths increases, by
+/// comparing the value entering the window with the one leaving it.
+pub fn count_smoothed_increases(
+    depths: impl IntoIterator<Item = i32>,
+    filter: Filter,
+    size: usize,
+) -> usize {
+    let mut window = VecDeque::with_capacity(size + 1);
+    let mut increases = 0;
+    for value in smooth(depths, filter) {
+        window.push_back(value);
+        if window.len() > size && window.pop_front().is_some_and(|leaving| value > leaving) {
+            increases += 1;
+        }
+    }
+    increases
+}
+
+/// Like `count_smoothed_increases`, reading the depths a line at a time.
+pub fn stream_smoothed_increases(
+    reader: &mut dyn BufRead,
+    cancel: &CancelToken,
+    filter: Filter,
+    size: usize,
+) -> Result<usize, SolveError> {
+    let depths = LineSource::new(reader, cancel).parse(integer::<i32>);
+    Ok(itertools::process_results(depths, |depths| {
+        count_smoothed_increases(depths, filter, size)
+    })?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::day1::count_window_increases;
+
+    static TEST_INPUT: [i32; 10] = [199, 200, 208, 210, 200, 207, 240, 269, 260, 263];
+
+    #[test]
+    fn test_parse() {
+        for filter in ["sma:3", "ema:0.5", "median:5", "hysteresis:2.5"] {
+            assert_eq!(filter.parse::<Filter>().unwrap().to_string(), filter);
+        }
+        assert!("sma:0".parse::<Filter>().is_err());
+        assert!("ema:1.5".parse::<Filter>().is_err());
+        assert!("hysteresis:-1".parse::<Filter>().is_err());
+        assert!("mean:3".parse::<Filter>().is_err());
+        assert!("sma".parse::<Filter>().is_err());
+    }
+
+    #[test]
+    fn test_moving_average() {
+        let smoothed: Vec<_> = smooth([1, 2, 3, 7], Filter::MovingAverage(2)).collect();
+        assert_eq!(smoothed, [1.5, 2.5, 5.0]);
+        assert_eq!(
+            count_smoothed_increases(TEST_INPUT, Filter::MovingAverage(3), 1),
+            count_window_increases(TEST_INPUT, 3)
+        );
+    }
+
+    #[test]
+    fn test_exponential_average() {
+        let smoothed: Vec<_> = smooth([10, 20, 20], Filter::ExponentialAverage(0.5)).collect();
+        assert_eq!(smoothed, [10.0, 15.0, 17.5]);
+    }
+
+    #[test]
+    fn test_median() {
+        let smoothed: Vec<_> = smooth([1, 9, 2, 3, 4], Filter::Median(3)).collect();
+        assert_eq!(smoothed, [2.0, 3.0, 3.0]);
+        let smoothed: Vec<_> = smooth([1, 9, 2, 3], Filter::Median(2)).collect();
+        assert_eq!(smoothed, [5.0, 5.5, 2.5]);
+    }
+
+    #[test]
+    fn test_hysteresis() {
+        let smoothed: Vec<_> = smooth([10, 11, 9, 13, 12, 20], Filter::Hysteresis(2.0)).collect();
+        assert_eq!(smoothed, [10.0, 10.0, 10.0, 13.0, 13.0, 20.0]);
+        assert_eq!(
+            count_smoothed_increases(TEST_INPUT, Filter::Hysteresis(5.0), 1),
+            4
+        );
+    }
+
+    #[test]
+    fn test_stream() {
+        let cancel = CancelToken::new();
+        let mut reader = "199\n200\n208\n210\n200\n207\n240\n269\n260\n263".as_bytes();
+        assert_eq!(
+            stream_smoothed_increases(&mut reader, &cancel, Filter::Median(3), 1),
+            Ok(4)
+        );
+    }
+}
@@ -1,6 +1,12 @@
 use crate::day10::Character::Curly;
+use crate::solvers::SolveError;
+use crate::utils::arith::{self, Overflow};
+use crate::utils::cancel::CancelToken;
+use crate::utils::parse::{ParseError, ParseResult, Separator, Span};
 use crate::utils::parse_lines;
+use crate::utils::stream::LineSource;
 use itertools::Itertools;
+use std::io::BufRead;
 use std::str::FromStr;
 
 #[derive(Debug, Eq, PartialEq)]
@@ -21,6 +27,15 @@ impl Character {
         }
     }
 
+    fn closing(&self) -> char {
+        match self {
+            Character::Paren => ')',
+            Character::Bracket => ']',
+            Character::Curly => '}',
+            Character::Angle => '>',
+        }
+    }
+
     fn auto_correct_score(&self) -> u64 {
         match self {
             Character::Paren => 1,
@@ -86,7 +101,7 @@ impl FromStr for Line {
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let mut stack = Vec::with_capacity(s.len() / 2);
-        for instruction in s.chars().map(|c| Instruction::from_char(c)) {
+        for instruction in s.chars().map(Instruction::from_char) {
             let instruction = instruction?;
             match instruction {
                 Instruction::Open(character) => stack.push(character),
@@ -115,18 +130,19 @@ impl Navigation {
     fn new(lines: Vec<Line>) -> Self {
         Self { lines }
     }
-    fn syntax_error_score(&self) -> u32 {
-        self.lines.iter().flat_map(Line::syntax_error_score).sum()
+    fn syntax_error_score(&self) -> Result<u64, Overflow> {
+        total_syntax_error_score(self.lines.iter().flat_map(Line::syntax_error_score))
     }
 
-    fn auto_correct_score(&self) -> u64 {
+    /// The middle score of the incomplete lines, None if there are none.
+    fn auto_correct_score(&self) -> Option<u64> {
         let scores = self
             .lines
             .iter()
             .flat_map(Line::auto_correct_score)
             .sorted()
             .collect_vec();
-        scores[scores.len() / 2]
+        scores.get(scores.len() / 2).copied()
     }
 }
 
@@ -138,17 +154,91 @@ impl FromStr for Navigation {
     }
 }
 
+/// The scores of the corrupt lines add up past a `u32` long before a stream ends.
+fn total_syntax_error_score(scores: impl Iterator<Item = u32>) -> Result<u64, Overflow> {
+    arith::sum(scores.map(|score| Ok(u64::from(score))))
+}
+
+fn parse_line(span: Span) -> ParseResult<Line> {
+    match span.chars().find(|(_, c)| !"()[]{}<>".contains(*c)) {
+        Some((position, c)) => Err(position.error(format!("expected a bracket, found {:?}", c))),
+        None => Ok(span.as_str().parse().expect("only brackets are left")),
+    }
+}
+
+pub fn lint(input: &str) -> Vec<ParseError> {
+    Span::new(input)
+        .split(Separator::Lines)
+        .into_iter()
+        .flat_map(|line| line.chars())
+        .filter(|(_, c)| !"()[]{}<>".contains(*c))
+        .map(|(position, c)| position.error(format!("expected a bracket, found {:?}", c)))
+        .collect()
+}
+
+pub fn part1(input: &str, _cancel: &CancelToken) -> Result<String, SolveError> {
+    let navigation = Navigation::new(parse_lines(input).collect());
+    Ok(navigation.syntax_error_score()?.to_string())
+}
+
+pub fn part2(input: &str, _cancel: &CancelToken) -> Result<String, SolveError> {
+    let navigation = Navigation::new(parse_lines(input).collect());
+    match navigation.auto_correct_score() {
+        Some(score) => Ok(score.to_string()),
+        None => Err(Span::new(input).error("no line is incomplete").into()),
+    }
+}
+
+pub fn stream_part1(reader: &mut dyn BufRead, cancel: &CancelToken) -> Result<String, SolveError> {
+    let lines = LineSource::new(reader, cancel).parse(parse_line);
+    let score = itertools::process_results(lines, |lines| {
+        total_syntax_error_score(lines.flat_map(|line| line.syntax_error_score()))
+    })??;
+    Ok(score.to_string())
+}
+
+/// How every line is classified, with the score it contributes.
+pub fn trace(input: &str) -> Result<String, SolveError> {
+    let navigation = Navigation::new(parse_lines(input).collect());
+    Ok(navigation
+        .lines
+        .iter()
+        .enumerate()
+        .map(|(index, line)| {
+            let class = match line {
+                Line::Corrupt(_, found) => format!(
+                    "corrupt, found {:?} ({})",
+                    found.closing(),
+                    found.syntax_error_score()
+                ),
+                Line::Incomplete(_, missing) => format!(
+                    "incomplete, completed by {:?} ({})",
+                    missing
+                        .iter()
+                        .rev()
+                        .map(Character::closing)
+                        .collect::<String>(),
+                    line.auto_correct_score().unwrap()
+                ),
+                Line::Valid(_) => "valid".to_owned(),
+            };
+            format!("{:>3}: {}\n", index + 1, class)
+        })
+        .collect::<String>())
+}
+
 pub fn run() {
     let navigation = Navigation::from_str(include_str!("data/10")).unwrap();
-    println!("{}", navigation.syntax_error_score());
-    println!("{}", navigation.auto_correct_score());
+    println!("{}", navigation.syntax_error_score().unwrap());
+    println!("{}", navigation.auto_correct_score().unwrap());
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::utils::snapshot::assert_snapshot;
 
-    static TEST_INPUT: &'static str = "[({(<(())[]>[[{[]{<()<>>
+    static TEST_INPUT: &str = "[({(<(())[]>[[{[]{<()<>>
 [(()[<>])]({[<{<<[]>>(
 {([(<{}[<>[]}>{[]{[(<()>
 (((({<>}<{<{<>}{[]{[]{}
@@ -165,7 +255,7 @@ mod tests {
             Navigation::from_str(TEST_INPUT)
                 .unwrap()
                 .syntax_error_score(),
-            26397
+            Ok(26397)
         )
     }
 
@@ -175,7 +265,42 @@ mod tests {
             Navigation::from_str(TEST_INPUT)
                 .unwrap()
                 .auto_correct_score(),
-            288957
-        )
+            Some(288957)
+        );
+        let cancel = CancelToken::new();
+        assert!(matches!(
+            part2("[]\n(]", &cancel),
+            Err(SolveError::Parse(errors)) if errors[0].message == "no line is incomplete"
+        ));
+    }
+
+    #[test]
+    fn test_classification_snapshot() {
+        assert_snapshot("day10_lines", &trace(include_str!("data/10")).unwrap());
+    }
+
+    #[test]
+    fn test_stream() {
+        let cancel = CancelToken::new();
+        let mut reader = TEST_INPUT.as_bytes();
+        assert_eq!(stream_part1(&mut reader, &cancel), Ok("26397".to_owned()));
+        let mut reader = "[]\n(x)".as_bytes();
+        assert!(matches!(
+            stream_part1(&mut reader, &cancel),
+            Err(SolveError::Parse(errors)) if (errors[0].line, errors[0].column) == (2, 2)
+        ));
+    }
+
+    #[test]
+    fn test_lint() {
+        assert!(lint(TEST_INPUT).is_empty());
+        let errors = lint("[()]\n[(x)]a");
+        assert_eq!(
+            errors
+                .iter()
+                .map(|err| (err.line, err.column))
+                .collect::<Vec<_>>(),
+            vec![(2, 3), (2, 6)]
+        );
     }
 }
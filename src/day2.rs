@@ -1,93 +1,148 @@
+use crate::solvers::SolveError;
+use crate::utils::arith::Overflow;
+use crate::utils::cancel::CancelToken;
+use crate::utils::parse::{self, integer, key_value, ParseError, ParseResult, Separator, Span};
 use crate::utils::parse_lines;
+use crate::utils::stream::LineSource;
+use models::{Aimed, NavigationModel, Simple};
+use std::fmt;
+use std::io::BufRead;
 use std::str::FromStr;
 
-#[derive(Debug)]
-enum Command {
+pub mod models;
+pub mod planner;
+pub mod safety;
+pub mod script;
+pub mod trajectory;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Command {
     Forward(i32),
     Up(i32),
     Down(i32),
+    /// Turns this many degrees, for models that steer.
+    Left(i32),
+    Right(i32),
 }
 
 impl FromStr for Command {
-    type Err = ();
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        s.split_once(" ")
-            .and_then(|(name, amount)| amount.parse::<i32>().ok().map(|num| (name, num)))
-            .and_then(|(name, num)| match name {
-                "forward" => Some(Command::Forward(num)),
-                "up" => Some(Command::Up(num)),
-                "down" => Some(Command::Down(num)),
-                _ => None,
-            })
-            .ok_or(())
+        parse_command(Span::new(s))
     }
 }
 
-#[derive(Debug, Default, PartialEq)]
-struct SimpleSubmarine {
-    distance: i32,
-    depth: i32,
-    aim: i32,
+impl fmt::Display for Command {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Command::Forward(num) => write!(f, "forward {}", num),
+            Command::Up(num) => write!(f, "up {}", num),
+            Command::Down(num) => write!(f, "down {}", num),
+            Command::Left(num) => write!(f, "left {}", num),
+            Command::Right(num) => write!(f, "right {}", num),
+        }
+    }
 }
 
-impl SimpleSubmarine {
-    pub fn new(distance: i32, depth: i32, aim: i32) -> Self {
-        Self {
-            distance,
-            depth,
-            aim,
-        }
+fn parse_command(span: Span) -> ParseResult<Command> {
+    let (name, num) = key_value(" ", Ok, integer)(span)?;
+    match name.as_str() {
+        "forward" => Ok(Command::Forward(num)),
+        "up" => Ok(Command::Up(num)),
+        "down" => Ok(Command::Down(num)),
+        "left" => Ok(Command::Left(num)),
+        "right" => Ok(Command::Right(num)),
+        other => Err(name.error(format!("unknown command {:?}", other))),
     }
-    pub fn navigate(&self, command: &Command, simple: bool) -> Self {
-        match command {
-            Command::Forward(num) => {
-                if simple {
-                    Self::new(self.distance + num, self.depth, self.aim)
-                } else {
-                    Self::new(self.distance + num, self.depth + (num * self.aim), self.aim)
-                }
-            }
-            Command::Up(num) => {
-                if simple {
-                    Self::new(self.distance, self.depth - num, self.aim)
-                } else {
-                    Self::new(self.distance, self.depth, self.aim - num)
-                }
-            }
-            Command::Down(num) => {
-                if simple {
-                    Self::new(self.distance, self.depth + num, self.aim)
-                } else {
-                    Self::new(self.distance, self.depth, self.aim + num)
-                }
-            }
-        }
+}
+
+/// A command for the puzzle's own models, which cannot steer.
+fn parse_flat_command(span: Span) -> ParseResult<Command> {
+    match parse_command(span)? {
+        Command::Left(_) | Command::Right(_) => Err(span.error("the puzzle's models cannot turn")),
+        command => Ok(command),
     }
+}
 
-    pub fn position(&self) -> i32 {
-        self.distance * self.depth
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Submarine {
+    /// How far forward the submarine is, for models that steer the length of its path.
+    pub distance: i32,
+    pub depth: i32,
+    pub aim: i32,
+    /// How far the submarine moves on its own after each command, for models with momentum.
+    pub speed: i32,
+    /// The direction the submarine faces, in degrees anticlockwise from the x axis from 0 up to
+    /// 360, for models that steer.
+    pub heading: i32,
+    /// Where the submarine is on the horizontal plane, for models that steer.
+    pub x: f64,
+    pub y: f64,
+}
+
+impl Submarine {
+    pub fn position(&self) -> i64 {
+        self.distance as i64 * self.depth as i64
     }
 }
 
-fn navigate(input: &str, simple: bool) -> SimpleSubmarine {
-    parse_lines::<Command>(input).fold(SimpleSubmarine::default(), |sub, cmd| {
-        sub.navigate(&cmd, simple)
-    })
+/// Where the commands take the submarine from the start, at the surface.
+pub fn follow(
+    commands: impl IntoIterator<Item = Command>,
+    model: &dyn NavigationModel,
+) -> Result<Submarine, Overflow> {
+    commands
+        .into_iter()
+        .try_fold(Submarine::default(), |sub, cmd| model.navigate(&sub, &cmd))
+}
+
+fn navigate(input: &str, model: &dyn NavigationModel) -> Result<Submarine, Overflow> {
+    follow(parse_lines::<Command>(input), model)
+}
+
+pub fn lint(input: &str) -> Vec<ParseError> {
+    parse::lint(Span::new(input), Separator::Lines, parse_flat_command)
+}
+
+static INPUT: &str = include_str!("data/2");
+
+pub fn part1(input: &str, _cancel: &CancelToken) -> Result<String, SolveError> {
+    Ok(navigate(input, &Simple)?.position().to_string())
+}
+
+pub fn part2(input: &str, _cancel: &CancelToken) -> Result<String, SolveError> {
+    Ok(navigate(input, &Aimed)?.position().to_string())
 }
 
-static INPUT: &'static str = include_str!("data/2");
+fn stream_navigate(
+    reader: &mut dyn BufRead,
+    cancel: &CancelToken,
+    model: &dyn NavigationModel,
+) -> Result<String, SolveError> {
+    let commands = LineSource::new(reader, cancel).parse(parse_flat_command);
+    let submarine = itertools::process_results(commands, |commands| follow(commands, model))??;
+    Ok(submarine.position().to_string())
+}
+
+pub fn stream_part1(reader: &mut dyn BufRead, cancel: &CancelToken) -> Result<String, SolveError> {
+    stream_navigate(reader, cancel, &Simple)
+}
+
+pub fn stream_part2(reader: &mut dyn BufRead, cancel: &CancelToken) -> Result<String, SolveError> {
+    stream_navigate(reader, cancel, &Aimed)
+}
 
 pub fn run() {
-    println!("{}", navigate(INPUT, true).position());
-    println!("{}", navigate(INPUT, false).position());
+    println!("{}", navigate(INPUT, &Simple).unwrap().position());
+    println!("{}", navigate(INPUT, &Aimed).unwrap().position());
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    static TEST_INPUT: &'static str = "forward 5
+    static TEST_INPUT: &str = "forward 5
 down 5
 forward 8
 up 3
@@ -96,11 +151,47 @@ forward 2";
 
     #[test]
     fn test_simple() {
-        assert_eq!(navigate(TEST_INPUT, true).position(), 150);
+        assert_eq!(navigate(TEST_INPUT, &Simple).unwrap().position(), 150);
     }
 
     #[test]
     fn test_aimed() {
-        assert_eq!(navigate(TEST_INPUT, false).position(), 900);
+        assert_eq!(navigate(TEST_INPUT, &Aimed).unwrap().position(), 900);
+    }
+
+    #[test]
+    fn test_stream() {
+        let cancel = CancelToken::new();
+        let mut reader = TEST_INPUT.as_bytes();
+        assert_eq!(stream_part1(&mut reader, &cancel), Ok("150".to_owned()));
+        let mut reader = TEST_INPUT.as_bytes();
+        assert_eq!(stream_part2(&mut reader, &cancel), Ok("900".to_owned()));
+        let input = "down 2\nforward 2000000000";
+        assert_eq!(
+            stream_part1(&mut input.as_bytes(), &cancel),
+            Ok("4000000000".to_owned())
+        );
+        let mut reader = input.as_bytes();
+        assert_eq!(
+            stream_part2(&mut reader, &cancel),
+            Err(SolveError::Overflow)
+        );
+        // the same on every path, not only when streaming
+        assert_eq!(part2(input, &cancel), Err(SolveError::Overflow));
+        assert_eq!(navigate(input, &Aimed), Err(Overflow));
+    }
+
+    #[test]
+    fn test_lint() {
+        assert!(lint(TEST_INPUT).is_empty());
+        let errors = lint("forward 5\nsideways 2\ndown x\nleft 90");
+        assert_eq!(
+            errors
+                .iter()
+                .map(|err| (err.line, err.column))
+                .collect::<Vec<_>>(),
+            vec![(2, 1), (3, 6), (4, 1)]
+        );
+        assert_eq!(errors[2].message, "the puzzle's models cannot turn");
     }
 }
@@ -0,0 +1,350 @@
+//! How the submarine responds to each command. The puzzle has two readings of the same
+//! commands, these and a few more are interchangeable so new ones need no changes to parsing.
+
+use super::{Command, Submarine};
+use crate::utils::arith::{add, mul, Overflow};
+use std::fmt;
+
+pub trait NavigationModel: fmt::Debug {
+    /// The submarine after following the command, or an error if any of its numbers would not
+    /// fit an `i32`.
+    fn navigate(&self, submarine: &Submarine, command: &Command) -> Result<Submarine, Overflow>;
+
+    /// Whether `left` and `right` turn the submarine, models that do not steer ignore them.
+    fn steers(&self) -> bool {
+        false
+    }
+
+    /// At least how many more commands, each by at most `max_step`, it takes the submarine to
+    /// reach the target distance and depth, None if it never can. The planner finds shortest
+    /// courses as long as this never overestimates, and searches blindly with the default.
+    fn lower_bound(
+        &self,
+        _submarine: &Submarine,
+        _target: (i32, i32),
+        _max_step: i32,
+    ) -> Option<u64> {
+        Some(0)
+    }
+}
+
+fn sub(lhs: i32, rhs: i32) -> Result<i32, Overflow> {
+    lhs.checked_sub(rhs).ok_or(Overflow)
+}
+
+/// How many commands of at most `max_step` add up to `amount`.
+fn steps(amount: i64, max_step: i32) -> u64 {
+    amount.unsigned_abs().div_ceil(max_step as u64)
+}
+
+/// Part 1: `up` and `down` change the depth directly.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Simple;
+
+impl NavigationModel for Simple {
+    fn navigate(&self, submarine: &Submarine, command: &Command) -> Result<Submarine, Overflow> {
+        Ok(match *command {
+            Command::Forward(num) => Submarine {
+                distance: add(&submarine.distance, &num)?,
+                ..*submarine
+            },
+            Command::Up(num) => Submarine {
+                depth: sub(submarine.depth, num)?,
+                ..*submarine
+            },
+            Command::Down(num) => Submarine {
+                depth: add(&submarine.depth, &num)?,
+                ..*submarine
+            },
+            Command::Left(_) | Command::Right(_) => *submarine,
+        })
+    }
+
+    fn lower_bound(&self, submarine: &Submarine, target: (i32, i32), max_step: i32) -> Option<u64> {
+        let distance = target.0 as i64 - submarine.distance as i64;
+        let depth = target.1 as i64 - submarine.depth as i64;
+        Some(steps(distance, max_step) + steps(depth, max_step))
+    }
+}
+
+/// Part 2: `up` and `down` change the aim, moving forward changes the depth by the aim.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Aimed;
+
+impl NavigationModel for Aimed {
+    fn navigate(&self, submarine: &Submarine, command: &Command) -> Result<Submarine, Overflow> {
+        Ok(match *command {
+            Command::Forward(num) => Submarine {
+                distance: add(&submarine.distance, &num)?,
+                depth: add(&submarine.depth, &mul(&num, &submarine.aim)?)?,
+                ..*submarine
+            },
+            Command::Up(num) => Submarine {
+                aim: sub(submarine.aim, num)?,
+                ..*submarine
+            },
+            Command::Down(num) => Submarine {
+                aim: add(&submarine.aim, &num)?,
+                ..*submarine
+            },
+            Command::Left(_) | Command::Right(_) => *submarine,
+        })
+    }
+
+    fn lower_bound(&self, submarine: &Submarine, target: (i32, i32), max_step: i32) -> Option<u64> {
+        let distance = target.0 as i64 - submarine.distance as i64;
+        let depth = target.1 as i64 - submarine.depth as i64;
+        // only moving changes the depth, and it takes a change of aim unless the current aim
+        // already leads there, which with no distance left takes moving there and back
+        let moves = match (distance, depth) {
+            (0, 0) => 0,
+            (0, _) => 2,
+            _ => steps(distance, max_step),
+        };
+        let turns = (depth != distance * submarine.aim as i64) as u64;
+        Some(moves + turns)
+    }
+}
+
+/// The submarine keeps moving: `forward` adds to its speed, `up` and `down` change the depth
+/// directly, and after every command it travels forward by its speed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Momentum;
+
+impl NavigationModel for Momentum {
+    fn navigate(&self, submarine: &Submarine, command: &Command) -> Result<Submarine, Overflow> {
+        let (speed, depth) = match *command {
+            Command::Forward(num) => (add(&submarine.speed, &num)?, submarine.depth),
+            Command::Up(num) => (submarine.speed, sub(submarine.depth, num)?),
+            Command::Down(num) => (submarine.speed, add(&submarine.depth, &num)?),
+            Command::Left(_) | Command::Right(_) => (submarine.speed, submarine.depth),
+        };
+        Ok(Submarine {
+            distance: add(&submarine.distance, &speed)?,
+            depth,
+            speed,
+            ..*submarine
+        })
+    }
+
+    fn lower_bound(&self, submarine: &Submarine, target: (i32, i32), max_step: i32) -> Option<u64> {
+        let distance = target.0 as i64 - submarine.distance as i64;
+        let depth = target.1 as i64 - submarine.depth as i64;
+        // every command changes the speed by at most a step, so after n of them the submarine
+        // has travelled n times its speed give or take n(n+1)/2 steps
+        let (speed, max_step) = (submarine.speed as i64, max_step as i64);
+        (steps(depth, max_step as i32)..).find(|&n| {
+            let n = n as i64;
+            (distance - n * speed).abs() <= max_step * n * (n + 1) / 2
+        })
+    }
+}
+
+/// Part 2 in three dimensions: `left` and `right` turn the submarine, and `forward` moves it
+/// along its heading. `distance` is the length of its path, backwards counts too.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Steered;
+
+impl NavigationModel for Steered {
+    fn navigate(&self, submarine: &Submarine, command: &Command) -> Result<Submarine, Overflow> {
+        // reduced first so that neither turning right nor adding to the heading can overflow
+        let turn = |degrees: i32| Submarine {
+            heading: (submarine.heading + degrees.rem_euclid(360)) % 360,
+            ..*submarine
+        };
+        match *command {
+            Command::Forward(num) => {
+                // exact along the axes, so straight courses do not pick up rounding errors
+                let (dx, dy) = match submarine.heading {
+                    0 => (1.0, 0.0),
+                    90 => (0.0, 1.0),
+                    180 => (-1.0, 0.0),
+                    270 => (0.0, -1.0),
+                    heading => {
+                        let radians = (heading as f64).to_radians();
+                        (radians.cos(), radians.sin())
+                    }
+                };
+                Ok(Submarine {
+                    distance: add(&submarine.distance, &num.checked_abs().ok_or(Overflow)?)?,
+                    depth: add(&submarine.depth, &mul(&num, &submarine.aim)?)?,
+                    x: submarine.x + num as f64 * dx,
+                    y: submarine.y + num as f64 * dy,
+                    ..*submarine
+                })
+            }
+            Command::Left(degrees) => Ok(turn(degrees)),
+            Command::Right(degrees) => Ok(turn(360 - degrees.rem_euclid(360))),
+            command => Aimed.navigate(submarine, &command),
+        }
+    }
+
+    fn steers(&self) -> bool {
+        true
+    }
+
+    fn lower_bound(&self, submarine: &Submarine, target: (i32, i32), max_step: i32) -> Option<u64> {
+        // the path only ever gets longer, and the depth changes by moving at some aim
+        let distance = target.0 as i64 - submarine.distance as i64;
+        let depth = target.1 as i64 - submarine.depth as i64;
+        if distance < 0 || (distance == 0 && depth != 0) {
+            return None;
+        }
+        Some(steps(distance, max_step) + (depth != 0 && submarine.aim == 0) as u64)
+    }
+}
+
+/// Another model that cannot take the submarine above the surface, it stops at depth 0.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Surfaced<M>(pub M);
+
+impl<M: NavigationModel> NavigationModel for Surfaced<M> {
+    fn navigate(&self, submarine: &Submarine, command: &Command) -> Result<Submarine, Overflow> {
+        let submarine = self.0.navigate(submarine, command)?;
+        Ok(Submarine {
+            depth: submarine.depth.max(0),
+            ..submarine
+        })
+    }
+
+    fn steers(&self) -> bool {
+        self.0.steers()
+    }
+
+    fn lower_bound(&self, submarine: &Submarine, target: (i32, i32), max_step: i32) -> Option<u64> {
+        // stopping at the surface can save changes of depth or aim but never of distance, so
+        // only the distance the inner model has left to cover is bounded
+        let level = Submarine {
+            aim: 0,
+            ..*submarine
+        };
+        self.0
+            .lower_bound(&level, (target.0, submarine.depth), max_step)
+    }
+}
+
+/// The names `model` knows, each also with a `surfaced-` prefix.
+pub static NAMES: [&str; 4] = ["simple", "aimed", "momentum", "3d"];
+
+/// The model with the given name, such as `aimed` or `surfaced-momentum`.
+pub fn model(name: &str) -> Option<Box<dyn NavigationModel>> {
+    match name.strip_prefix("surfaced-") {
+        Some("simple") => Some(Box::new(Surfaced(Simple))),
+        Some("aimed") => Some(Box::new(Surfaced(Aimed))),
+        Some("momentum") => Some(Box::new(Surfaced(Momentum))),
+        Some("3d") => Some(Box::new(Surfaced(Steered))),
+        Some(_) => None,
+        None => match name {
+            "simple" => Some(Box::new(Simple)),
+            "aimed" => Some(Box::new(Aimed)),
+            "momentum" => Some(Box::new(Momentum)),
+            "3d" => Some(Box::new(Steered)),
+            _ => None,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::day2::navigate;
+
+    static TEST_INPUT: &str = "forward 5
+down 5
+forward 8
+up 3
+down 8
+forward 2";
+
+    fn submarine(distance: i32, depth: i32, aim: i32, speed: i32) -> Submarine {
+        Submarine {
+            distance,
+            depth,
+            aim,
+            speed,
+            ..Submarine::default()
+        }
+    }
+
+    #[test]
+    fn test_momentum() {
+        assert_eq!(
+            navigate(TEST_INPUT, &Momentum).unwrap(),
+            submarine(5 + 5 + 13 + 13 + 13 + 15, 10, 0, 15)
+        );
+    }
+
+    #[test]
+    fn test_surfaced() {
+        let input = "up 3\ndown 1\nforward 2";
+        assert_eq!(navigate(input, &Simple).unwrap(), submarine(2, -2, 0, 0));
+        assert_eq!(
+            navigate(input, &Surfaced(Simple)).unwrap(),
+            submarine(2, 1, 0, 0)
+        );
+        assert_eq!(navigate(input, &Aimed).unwrap(), submarine(2, -4, -2, 0));
+        assert_eq!(
+            navigate(input, &Surfaced(Aimed)).unwrap(),
+            submarine(2, 0, -2, 0)
+        );
+    }
+
+    #[test]
+    fn test_steered() {
+        // turns are ignored by the flat models
+        let input = "forward 5\nleft 90\ndown 2\nforward 3\nright 180\nforward 4";
+        assert_eq!(navigate(input, &Aimed).unwrap(), submarine(12, 14, 2, 0));
+        let submarine = navigate(input, &Steered).unwrap();
+        assert_eq!(
+            (submarine.distance, submarine.depth, submarine.heading),
+            (12, 14, 270)
+        );
+        assert_eq!((submarine.x, submarine.y), (5.0, -1.0));
+
+        let submarine = navigate("left 45\nforward 10\nright 405\nforward -2", &Steered).unwrap();
+        assert_eq!((submarine.distance, submarine.heading), (12, 0));
+        assert!((submarine.x - (50f64.sqrt() - 2.0)).abs() < 1e-9);
+        assert!((submarine.y - 50f64.sqrt()).abs() < 1e-9);
+
+        let submarine = navigate(
+            "right 2147483647\nleft -2147483648\nright -2147483648",
+            &Steered,
+        )
+        .unwrap();
+        // the last two turns cancel out, and 2147483647 is 127 past a multiple of 360
+        assert_eq!(submarine.heading, 360 - 127);
+
+        // moving backwards the length of the path overflows, not only the depth
+        let input = "forward -2147483648";
+        assert_eq!(navigate(input, &Aimed).unwrap().distance, i32::MIN);
+        assert_eq!(navigate(input, &Steered), Err(Overflow));
+        let input = "down 2\nforward 2000000000";
+        assert_eq!(navigate(input, &Steered), Err(Overflow));
+        assert_eq!(
+            navigate(input, &Surfaced(Momentum)).unwrap().speed,
+            2000000000
+        );
+        assert_eq!(
+            navigate("forward 2000000000\nup 1", &Momentum),
+            Err(Overflow)
+        );
+    }
+
+    #[test]
+    fn test_model() {
+        for name in NAMES {
+            let steers = model(name).unwrap().steers();
+            assert_eq!(steers, name == "3d");
+            let surfaced = model(&format!("surfaced-{}", name)).unwrap();
+            assert_eq!(surfaced.steers(), steers);
+        }
+        assert_eq!(
+            navigate(TEST_INPUT, model("aimed").unwrap().as_ref())
+                .unwrap()
+                .position(),
+            900
+        );
+        assert!(model("surfaced-").is_none());
+        assert!(model("sideways").is_none());
+    }
+}
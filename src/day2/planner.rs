@@ -0,0 +1,241 @@
+//! Works out commands that take the submarine to a given place, the reverse of navigating.
+
+use super::models::NavigationModel;
+use super::{Command, Submarine};
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+
+/// How many states the search may visit before giving up.
+const MAX_STATES: usize = 2_000_000;
+
+/// The largest step the planner tries, each state has four commands per unit of step.
+const MAX_STEP: i32 = 1_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Constraints {
+    /// The largest amount any one command may have.
+    pub max_step: i32,
+    /// Whether the submarine may rise above the surface on the way.
+    pub negative_depth: bool,
+    /// The steepest the submarine may dive or climb, as the largest aim either way. With the
+    /// aimed model every unit forward changes the depth by the aim.
+    pub max_aim: Option<i32>,
+}
+
+impl Default for Constraints {
+    fn default() -> Self {
+        Self {
+            max_step: 10,
+            negative_depth: false,
+            max_aim: None,
+        }
+    }
+}
+
+/// A shortest course from the surface to the target distance and depth, in any aim. Searched
+/// with A*, guided by the model's lower bound, over `forward`, `back`, `up` and `down` by 1 up
+/// to the step size, among courses that stay within a step of the box between the start and
+/// the target. None if there is no such course, the search is too large, or the target is so
+/// far that the submarine's numbers could overflow on the way.
+pub fn plan(
+    model: &dyn NavigationModel,
+    target: (i32, i32),
+    constraints: &Constraints,
+) -> Option<Vec<Command>> {
+    let step = constraints.max_step;
+    if !(1..=MAX_STEP).contains(&step) {
+        return None;
+    }
+    let (distance, depth) = target;
+    let max_aim = match constraints.max_aim {
+        Some(aim) => aim.unsigned_abs() as i64,
+        None => depth.unsigned_abs().max(step as u32) as i64,
+    };
+    // the largest numbers a state within the box can reach in one more command, the speed is
+    // kept under the distance plus a step so the distance stays under twice that
+    let (distance_limit, step_limit) = (distance.unsigned_abs() as i64, step as i64);
+    let depth_limit = depth.unsigned_abs() as i64 + step_limit;
+    if 2 * (distance_limit + 2 * step_limit) > i32::MAX as i64
+        || depth_limit + step_limit * max_aim.max(1) > i32::MAX as i64
+        || max_aim + step_limit > i32::MAX as i64
+    {
+        return None;
+    }
+    let max_aim = max_aim as i32;
+    let depth_floor = if constraints.negative_depth {
+        depth.min(0) - step
+    } else {
+        0
+    };
+    let max_speed = distance.abs() + step;
+    let within = |sub: &Submarine| {
+        (distance.min(0) - step..=distance.max(0) + step).contains(&sub.distance)
+            && (depth_floor..=depth.max(0) + step).contains(&sub.depth)
+            && sub.aim.abs() <= max_aim
+            && sub.speed.abs() <= max_speed
+    };
+    let commands: Vec<_> = (1..=step)
+        .flat_map(|num| {
+            [
+                Command::Forward(num),
+                Command::Forward(-num),
+                Command::Up(num),
+                Command::Down(num),
+            ]
+        })
+        .collect();
+    // courses are planned without turning, so where the submarine is on the plane follows from
+    // the rest of its state
+    let key = |sub: &Submarine| (sub.distance, sub.depth, sub.aim, sub.speed, sub.heading);
+    let start = Submarine::default();
+    // every state reached, with the one before it and the command between them
+    let mut states: Vec<(Submarine, Option<(usize, Command)>)> = vec![(start, None)];
+    let mut best = HashMap::from([(key(&start), 0)]);
+    let mut queue = BinaryHeap::new();
+    // ties go to the state furthest along, which is the closest to the target
+    queue.push(Reverse((
+        model.lower_bound(&start, target, step)?,
+        Reverse(0),
+        0,
+    )));
+    while let Some(Reverse((_, Reverse(length), index))) = queue.pop() {
+        let sub = states[index].0;
+        if best[&key(&sub)] < length {
+            continue;
+        }
+        if (sub.distance, sub.depth) == target {
+            let mut course = Vec::new();
+            let mut current = index;
+            while let Some((before, command)) = states[current].1 {
+                course.push(command);
+                current = before;
+            }
+            course.reverse();
+            return Some(course);
+        }
+        for command in &commands {
+            // a command that overflows leads nowhere the target can be
+            let next = match model.navigate(&sub, command) {
+                Ok(next) => next,
+                Err(_) => continue,
+            };
+            let known = best.get(&key(&next)).copied();
+            if !within(&next) || known.is_some_and(|known| known <= length + 1) {
+                continue;
+            }
+            let remaining = match model.lower_bound(&next, target, step) {
+                Some(remaining) => remaining,
+                None => continue,
+            };
+            if states.len() >= MAX_STATES {
+                return None;
+            }
+            best.insert(key(&next), length + 1);
+            states.push((next, Some((index, *command))));
+            queue.push(Reverse((
+                length + 1 + remaining,
+                Reverse(length + 1),
+                states.len() - 1,
+            )));
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::day2::follow;
+    use crate::day2::models::{Aimed, Momentum, Simple, Steered, Surfaced};
+    use crate::day2::trajectory::Trajectory;
+
+    fn reaches(
+        model: &dyn NavigationModel,
+        target: (i32, i32),
+        constraints: &Constraints,
+    ) -> usize {
+        let course = plan(model, target, constraints).unwrap();
+        let end = follow(course.iter().copied(), model).unwrap();
+        assert_eq!((end.distance, end.depth), target);
+        course.len()
+    }
+
+    #[test]
+    fn test_plan() {
+        let constraints = Constraints::default();
+        assert_eq!(
+            plan(&Simple, (10, 5), &constraints),
+            Some(vec![Command::Down(5), Command::Forward(10)])
+        );
+        assert_eq!(
+            plan(&Aimed, (10, 20), &constraints),
+            Some(vec![Command::Down(2), Command::Forward(10)])
+        );
+        assert_eq!(plan(&Simple, (0, 0), &constraints), Some(vec![]));
+        assert_eq!(reaches(&Simple, (-25, 0), &constraints), 3);
+        assert_eq!(reaches(&Momentum, (15, 0), &constraints), 2);
+    }
+
+    #[test]
+    fn test_far_targets() {
+        let constraints = Constraints::default();
+        assert_eq!(reaches(&Aimed, (100, 100), &constraints), 11);
+        assert_eq!(reaches(&Simple, (2000, 1000), &constraints), 300);
+        assert_eq!(reaches(&Aimed, (300, 100), &constraints), 31);
+        assert!(reaches(&Momentum, (300, 100), &constraints) <= 14);
+        assert_eq!(reaches(&Steered, (100, 100), &constraints), 11);
+        assert_eq!(reaches(&Surfaced(Aimed), (300, 100), &constraints), 31);
+    }
+
+    #[test]
+    fn test_overflow() {
+        let constraints = Constraints::default();
+        assert_eq!(plan(&Simple, (i32::MAX, 0), &constraints), None);
+        assert_eq!(plan(&Aimed, (0, i32::MIN), &constraints), None);
+        assert_eq!(plan(&Momentum, (i32::MIN, i32::MAX), &constraints), None);
+        let constraints = Constraints {
+            max_step: i32::MAX,
+            ..Constraints::default()
+        };
+        assert_eq!(plan(&Simple, (10, 0), &constraints), None);
+    }
+
+    #[test]
+    fn test_constraints() {
+        let constraints = Constraints {
+            max_step: 3,
+            ..Constraints::default()
+        };
+        assert_eq!(reaches(&Simple, (7, 0), &constraints), 3);
+
+        assert_eq!(plan(&Simple, (0, -3), &Constraints::default()), None);
+        let constraints = Constraints {
+            negative_depth: true,
+            ..Constraints::default()
+        };
+        assert_eq!(
+            plan(&Simple, (0, -3), &constraints),
+            Some(vec![Command::Up(3)])
+        );
+        let constraints = Constraints::default();
+        let course = plan(&Aimed, (4, 3), &constraints).unwrap();
+        assert!(Trajectory::record(course, &Aimed)
+            .unwrap()
+            .steps
+            .iter()
+            .all(|(_, sub)| sub.depth >= 0));
+
+        assert_eq!(reaches(&Aimed, (1, 5), &Constraints::default()), 2);
+        let constraints = Constraints {
+            max_aim: Some(2),
+            ..Constraints::default()
+        };
+        assert_eq!(reaches(&Aimed, (1, 5), &constraints), 3);
+        let course = plan(&Aimed, (1, 5), &constraints).unwrap();
+        assert!(Trajectory::record(course, &Aimed)
+            .unwrap()
+            .steps
+            .iter()
+            .all(|(_, sub)| sub.aim.abs() <= 2));
+    }
+}
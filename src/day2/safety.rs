@@ -0,0 +1,213 @@
+//! Limits the submarine should stay within, checked after every command.
+
+use super::models::NavigationModel;
+use super::{Command, Submarine};
+use crate::utils::arith::Overflow;
+use std::fmt;
+
+/// Every limit is optional, by default nothing is checked.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Limits {
+    /// 0 keeps the submarine from going above the surface.
+    pub min_depth: Option<i32>,
+    pub max_depth: Option<i32>,
+    /// The largest aim either way.
+    pub max_aim: Option<i32>,
+    /// The furthest either way.
+    pub max_distance: Option<i32>,
+}
+
+/// A limit that was broken, with its value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Limit {
+    MinDepth(i32),
+    MaxDepth(i32),
+    MaxAim(i32),
+    MaxDistance(i32),
+}
+
+impl fmt::Display for Limit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Limit::MinDepth(depth) => write!(f, "shallower than {}", depth),
+            Limit::MaxDepth(depth) => write!(f, "deeper than {}", depth),
+            Limit::MaxAim(aim) => write!(f, "aim beyond {}", aim),
+            Limit::MaxDistance(distance) => write!(f, "further than {}", distance),
+        }
+    }
+}
+
+impl Limits {
+    /// The limits the submarine is outside of.
+    pub fn broken(&self, submarine: &Submarine) -> Vec<Limit> {
+        let checks = [
+            self.min_depth
+                .filter(|&min| submarine.depth < min)
+                .map(Limit::MinDepth),
+            self.max_depth
+                .filter(|&max| submarine.depth > max)
+                .map(Limit::MaxDepth),
+            self.max_aim
+                .filter(|&max| submarine.aim.unsigned_abs() > max.unsigned_abs())
+                .map(Limit::MaxAim),
+            self.max_distance
+                .filter(|&max| submarine.distance.unsigned_abs() > max.unsigned_abs())
+                .map(Limit::MaxDistance),
+        ];
+        checks.into_iter().flatten().collect()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Violation {
+    /// The number of the command, from 1.
+    pub step: usize,
+    pub command: Command,
+    /// The submarine right after the command.
+    pub submarine: Submarine,
+    pub limits: Vec<Limit>,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let limits: Vec<_> = self.limits.iter().map(Limit::to_string).collect();
+        write!(
+            f,
+            "command {} ({}) leaves the submarine at distance {} depth {} aim {}: {}",
+            self.step,
+            self.command,
+            self.submarine.distance,
+            self.submarine.depth,
+            self.submarine.aim,
+            limits.join(", ")
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Checked {
+    /// Where the submarine ended up, or where it stopped in strict mode.
+    pub submarine: Submarine,
+    pub violations: Vec<Violation>,
+    /// Whether strict mode stopped the run at a violation, even one by the last command.
+    pub stopped: bool,
+}
+
+/// Navigates while checking the limits after every command. In strict mode the run stops at the
+/// first command that breaks one.
+pub fn check(
+    commands: impl IntoIterator<Item = Command>,
+    model: &dyn NavigationModel,
+    limits: &Limits,
+    strict: bool,
+) -> Result<Checked, Overflow> {
+    let mut checked = Checked {
+        submarine: Submarine::default(),
+        violations: Vec::new(),
+        stopped: false,
+    };
+    for (index, command) in commands.into_iter().enumerate() {
+        checked.submarine = model.navigate(&checked.submarine, &command)?;
+        let broken = limits.broken(&checked.submarine);
+        if broken.is_empty() {
+            continue;
+        }
+        checked.violations.push(Violation {
+            step: index + 1,
+            command,
+            submarine: checked.submarine,
+            limits: broken,
+        });
+        if strict {
+            checked.stopped = true;
+            break;
+        }
+    }
+    Ok(checked)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::day2::models::{Aimed, Simple};
+    use crate::day2::script::compile;
+
+    static TEST_INPUT: &str = "forward 5
+down 5
+forward 8
+up 3
+down 8
+forward 2";
+
+    #[test]
+    fn test_check() {
+        let commands = compile(TEST_INPUT).unwrap();
+        let limits = Limits {
+            max_depth: Some(50),
+            max_aim: Some(9),
+            ..Limits::default()
+        };
+        let checked = check(commands.iter().copied(), &Aimed, &limits, false).unwrap();
+        assert_eq!(checked.submarine.position(), 900);
+        assert!(!checked.stopped);
+        assert_eq!(
+            checked
+                .violations
+                .iter()
+                .map(|violation| (violation.step, violation.limits.clone()))
+                .collect::<Vec<_>>(),
+            [
+                (5, vec![Limit::MaxAim(9)]),
+                (6, vec![Limit::MaxDepth(50), Limit::MaxAim(9)])
+            ]
+        );
+        assert_eq!(
+            checked.violations[0].to_string(),
+            "command 5 (down 8) leaves the submarine at distance 13 depth 40 aim 10: aim beyond 9"
+        );
+        assert!(check(commands, &Aimed, &Limits::default(), true)
+            .unwrap()
+            .violations
+            .is_empty());
+    }
+
+    #[test]
+    fn test_strict() {
+        let commands = compile("down 2\nup 3\nforward 4\nforward 20").unwrap();
+        let limits = Limits {
+            min_depth: Some(0),
+            max_distance: Some(10),
+            ..Limits::default()
+        };
+        let checked = check(commands.iter().copied(), &Simple, &limits, false).unwrap();
+        assert_eq!(checked.violations.len(), 3);
+        let checked = check(commands.iter().copied(), &Simple, &limits, true).unwrap();
+        assert!(checked.stopped);
+        assert_eq!(checked.submarine.depth, -1);
+        assert_eq!(
+            checked.violations,
+            [Violation {
+                step: 2,
+                command: Command::Up(3),
+                submarine: checked.submarine,
+                limits: vec![Limit::MinDepth(0)],
+            }]
+        );
+        let checked = check(commands.iter().copied().take(2), &Simple, &limits, true).unwrap();
+        assert!(checked.stopped);
+        assert!(
+            !check(commands.into_iter().take(1), &Simple, &limits, true)
+                .unwrap()
+                .stopped
+        );
+
+        let limits = Limits {
+            max_distance: Some(3),
+            ..Limits::default()
+        };
+        let commands = compile("back 2\nback 2").unwrap();
+        let checked = check(commands, &Simple, &limits, false).unwrap();
+        assert_eq!(checked.violations.len(), 1);
+        assert_eq!(checked.violations[0].limits, [Limit::MaxDistance(3)]);
+    }
+}
@@ -0,0 +1,394 @@
+//! A small language for scripting manoeuvres, which compiles to the puzzle's commands. Plain
+//! puzzle inputs are valid scripts.
+//!
+//! ```text
+//! # everything after a # is a comment
+//! let step = 2
+//! macro dive {
+//!     down step
+//!     forward 5
+//! }
+//! repeat 3 {
+//!     dive
+//!     back 1
+//! }
+//! ```
+//!
+//! `back N` moves backwards, as `forward` with a negative amount. `left N` and `right N` turn by
+//! N degrees, for models that steer. Amounts and repeat counts are integers or variables, which
+//! are set by `let` and can be set again. Macros may be used before they are defined, they see
+//! the variables as they are where they are used.
+
+use super::Command;
+use crate::utils::parse::{integer, ParseResult, Separator, Span};
+use std::collections::HashMap;
+
+/// How many statements and repeat iterations a script may run, so a typo in a repeat count
+/// cannot hang.
+const MAX_STEPS: usize = 10_000_000;
+
+static KEYWORDS: [&str; 9] = [
+    "forward", "up", "down", "back", "left", "right", "let", "repeat", "macro",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Verb {
+    Forward,
+    Up,
+    Down,
+    Back,
+    Left,
+    Right,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Value<'a> {
+    Literal(Span<'a>, i32),
+    Variable(Span<'a>),
+}
+
+impl<'a> Value<'a> {
+    fn span(&self) -> Span<'a> {
+        match *self {
+            Value::Literal(span, _) | Value::Variable(span) => span,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Statement<'a> {
+    Move(Verb, Value<'a>),
+    Let(Span<'a>, Value<'a>),
+    Repeat(Value<'a>, Vec<Statement<'a>>),
+    Call(Span<'a>),
+}
+
+impl<'a> Statement<'a> {
+    /// Where errors running the statement point.
+    fn span(&self) -> Span<'a> {
+        match self {
+            Statement::Move(_, value) | Statement::Repeat(value, _) => value.span(),
+            Statement::Let(name, _) | Statement::Call(name) => *name,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Script<'a> {
+    statements: Vec<Statement<'a>>,
+    macros: HashMap<&'a str, Vec<Statement<'a>>>,
+}
+
+fn identifier(span: Span) -> ParseResult<Span> {
+    let text = span.as_str();
+    let valid = text.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && text.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if !valid {
+        return Err(span.error(format!("expected a name, found {:?}", text)));
+    }
+    if KEYWORDS.contains(&text) {
+        return Err(span.error(format!("{:?} is a keyword, it cannot be a name", text)));
+    }
+    Ok(span)
+}
+
+fn value(span: Span) -> ParseResult<Value> {
+    if span
+        .as_str()
+        .starts_with(|c: char| c.is_ascii_digit() || c == '-')
+    {
+        integer(span).map(|num| Value::Literal(span, num))
+    } else {
+        identifier(span).map(Value::Variable)
+    }
+}
+
+impl<'a> Script<'a> {
+    pub fn parse(source: &'a str) -> ParseResult<Self> {
+        let mut script = Script {
+            statements: Vec::new(),
+            macros: HashMap::new(),
+        };
+        let mut lines = Span::new(source).split(Separator::Lines).into_iter();
+        script.statements = script.block(&mut lines, None)?;
+        Ok(script)
+    }
+
+    /// Parses statements up to the `}` closing the block opened at `opened`, or up to the end
+    /// of the script at the top level.
+    fn block(
+        &mut self,
+        lines: &mut impl Iterator<Item = Span<'a>>,
+        opened: Option<Span<'a>>,
+    ) -> ParseResult<Vec<Statement<'a>>> {
+        let mut statements = Vec::new();
+        while let Some(line) = lines.next() {
+            let code = match line.as_str().find('#') {
+                Some(index) => line.split_at(index).0,
+                None => line,
+            };
+            let tokens = code.split(Separator::Whitespace);
+            let words: Vec<_> = tokens.iter().map(Span::as_str).collect();
+            let statement = match (words.as_slice(), tokens.as_slice()) {
+                ([], _) => continue,
+                (["}"], [close]) => {
+                    return match opened {
+                        Some(_) => Ok(statements),
+                        None => Err(close.error("unexpected }, no block is open")),
+                    }
+                }
+                (["repeat", _, "{"], [_, count, open]) => {
+                    Statement::Repeat(value(*count)?, self.block(lines, Some(*open))?)
+                }
+                (["macro", _, "{"], [keyword, name, open]) => {
+                    if opened.is_some() {
+                        return Err(keyword.error("macros can only be defined at the top level"));
+                    }
+                    let name = identifier(*name)?;
+                    let body = self.block(lines, Some(*open))?;
+                    if self.macros.insert(name.as_str(), body).is_some() {
+                        return Err(
+                            name.error(format!("macro {:?} is already defined", name.as_str()))
+                        );
+                    }
+                    continue;
+                }
+                (["let", _, "=", _], [_, name, _, amount]) => {
+                    Statement::Let(identifier(*name)?, value(*amount)?)
+                }
+                (
+                    [verb @ ("forward" | "up" | "down" | "back" | "left" | "right"), _],
+                    [_, amount],
+                ) => {
+                    let verb = match *verb {
+                        "forward" => Verb::Forward,
+                        "up" => Verb::Up,
+                        "down" => Verb::Down,
+                        "back" => Verb::Back,
+                        "left" => Verb::Left,
+                        _ => Verb::Right,
+                    };
+                    Statement::Move(verb, value(*amount)?)
+                }
+                ([_], [name]) => Statement::Call(identifier(*name)?),
+                (_, [first, ..]) => {
+                    return Err(first.error(format!("unknown command {:?}", code.trim().as_str())))
+                }
+                (_, []) => unreachable!(),
+            };
+            statements.push(statement);
+        }
+        match opened {
+            Some(open) => Err(open.error("this block is never closed with }")),
+            None => Ok(statements),
+        }
+    }
+
+    /// Runs the script, expanding repeats and macros into the commands they stand for.
+    pub fn lower(&self) -> ParseResult<Vec<Command>> {
+        let mut lowering = Lowering {
+            script: self,
+            variables: HashMap::new(),
+            calls: Vec::new(),
+            commands: Vec::new(),
+            steps: 0,
+        };
+        lowering.run(&self.statements)?;
+        Ok(lowering.commands)
+    }
+}
+
+struct Lowering<'s, 'a> {
+    script: &'s Script<'a>,
+    variables: HashMap<&'a str, i32>,
+    /// The macros being expanded, innermost last.
+    calls: Vec<&'a str>,
+    commands: Vec<Command>,
+    steps: usize,
+}
+
+impl<'a> Lowering<'_, 'a> {
+    fn value(&self, value: Value<'a>) -> ParseResult<i32> {
+        match value {
+            Value::Literal(_, num) => Ok(num),
+            Value::Variable(name) => self
+                .variables
+                .get(name.as_str())
+                .copied()
+                .ok_or_else(|| name.error(format!("unknown variable {:?}", name.as_str()))),
+        }
+    }
+
+    /// Counts a step against `MAX_STEPS`, blaming `span` if it is one too many.
+    fn step(&mut self, span: Span) -> ParseResult<()> {
+        self.steps += 1;
+        if self.steps > MAX_STEPS {
+            return Err(span.error(format!("the script runs for more than {} steps", MAX_STEPS)));
+        }
+        Ok(())
+    }
+
+    fn run(&mut self, statements: &[Statement<'a>]) -> ParseResult<()> {
+        for statement in statements {
+            self.step(statement.span())?;
+            match statement {
+                Statement::Move(verb, amount) => {
+                    let num = self.value(*amount)?;
+                    self.commands.push(match verb {
+                        Verb::Forward => Command::Forward(num),
+                        Verb::Up => Command::Up(num),
+                        Verb::Down => Command::Down(num),
+                        Verb::Back => Command::Forward(num.saturating_neg()),
+                        Verb::Left => Command::Left(num),
+                        Verb::Right => Command::Right(num),
+                    });
+                }
+                Statement::Let(name, amount) => {
+                    let num = self.value(*amount)?;
+                    self.variables.insert(name.as_str(), num);
+                }
+                Statement::Repeat(count, body) => {
+                    let times = self.value(*count)?;
+                    if times < 0 {
+                        return Err(count.span().error(format!("cannot repeat {} times", times)));
+                    }
+                    for _ in 0..times {
+                        // an empty body takes no steps, the iterations still do
+                        self.step(count.span())?;
+                        self.run(body)?;
+                    }
+                }
+                Statement::Call(name) => {
+                    let script = self.script;
+                    let body = script.macros.get(name.as_str()).ok_or_else(|| {
+                        name.error(format!("unknown command or macro {:?}", name.as_str()))
+                    })?;
+                    if self.calls.contains(&name.as_str()) {
+                        return Err(name.error(format!("macro {:?} uses itself", name.as_str())));
+                    }
+                    self.calls.push(name.as_str());
+                    self.run(body)?;
+                    self.calls.pop();
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Parses a script and lowers it to commands.
+pub fn compile(source: &str) -> ParseResult<Vec<Command>> {
+    Script::parse(source)?.lower()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::day2::{follow, models::Aimed};
+
+    fn error(source: &str) -> (usize, usize, String) {
+        let err = compile(source).unwrap_err();
+        (err.line, err.column, err.message)
+    }
+
+    #[test]
+    fn test_plain_input() {
+        let input = "forward 5\ndown 5\nforward 8\nup 3\ndown 8\nforward 2\n";
+        assert_eq!(
+            follow(compile(input).unwrap(), &Aimed).unwrap().position(),
+            900
+        );
+    }
+
+    #[test]
+    fn test_compile() {
+        let source = "# a dive
+let step = 2
+macro dive {
+    down step   # deeper each time
+    forward 5
+}
+
+repeat 2 {
+    dive
+    back 1
+    let step = 3
+}
+";
+        assert_eq!(
+            compile(source).unwrap(),
+            [
+                Command::Down(2),
+                Command::Forward(5),
+                Command::Forward(-1),
+                Command::Down(3),
+                Command::Forward(5),
+                Command::Forward(-1),
+            ]
+        );
+        assert_eq!(
+            compile("dive\nmacro dive {\nup 1\n}").unwrap(),
+            [Command::Up(1)]
+        );
+        assert_eq!(compile("repeat 0 {\nup 1\n}").unwrap(), []);
+        assert_eq!(
+            compile("repeat 2 {\n  left 90\n}\nright 45").unwrap(),
+            [Command::Left(90), Command::Left(90), Command::Right(45)]
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(
+            error("forward 1\nsideways 2"),
+            (2, 1, "unknown command \"sideways 2\"".to_owned())
+        );
+        assert_eq!(
+            error("repeat 2 {\n  up 1\n"),
+            (1, 10, "this block is never closed with }".to_owned())
+        );
+        assert_eq!(
+            error("up 1\n}"),
+            (2, 1, "unexpected }, no block is open".to_owned())
+        );
+        assert_eq!(
+            error("let a = 1\ndown b"),
+            (2, 6, "unknown variable \"b\"".to_owned())
+        );
+        assert_eq!(
+            error("macro a {\n  b\n}\nmacro b {\n  a\n}\na"),
+            (5, 3, "macro \"a\" uses itself".to_owned())
+        );
+        assert_eq!(
+            error("repeat 1 {\n  macro a {\n  }\n}"),
+            (
+                2,
+                3,
+                "macros can only be defined at the top level".to_owned()
+            )
+        );
+        assert_eq!(
+            error("let n = -1\nrepeat n {\n}"),
+            (2, 8, "cannot repeat -1 times".to_owned())
+        );
+        assert_eq!(
+            error("let up = 1"),
+            (1, 5, "\"up\" is a keyword, it cannot be a name".to_owned())
+        );
+        assert_eq!(error("repeat -2 {\n}").1, 8);
+        assert_eq!(
+            error("repeat 100000 {\n  repeat 1000 {\n    up 1\n  }\n}").2,
+            "the script runs for more than 10000000 steps"
+        );
+        assert_eq!(
+            error("repeat 2147483647 {\n  repeat 2147483647 {\n  }\n}"),
+            (
+                2,
+                10,
+                "the script runs for more than 10000000 steps".to_owned()
+            )
+        );
+        assert_eq!(error("down x1y").2, "unknown variable \"x1y\"");
+        assert_eq!(error("forward 1.5").1, 9);
+    }
+}
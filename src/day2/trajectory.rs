@@ -0,0 +1,239 @@
+//! The path the submarine takes rather than only where it ends up, to inspect dives and compare
+//! how models read the same commands.
+
+use super::models::NavigationModel;
+use super::{Command, Submarine};
+use crate::utils::arith::Overflow;
+use crate::utils::svg::Svg;
+use std::fmt::Write;
+
+/// The size of a depth profile, the paths are scaled to fit.
+const PROFILE_WIDTH: u32 = 800;
+const PROFILE_HEIGHT: u32 = 400;
+/// The width and height of a plan view, it keeps both axes on the same scale.
+const PLAN_SIZE: u32 = 400;
+
+/// Colours for the paths of a picture, in turn.
+static COLOURS: [&str; 4] = ["steelblue", "red", "seagreen", "darkorange"];
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Trajectory {
+    /// Every command with the submarine after it, the submarine starts at the surface.
+    pub steps: Vec<(Command, Submarine)>,
+}
+
+impl Trajectory {
+    pub fn record(
+        commands: impl IntoIterator<Item = Command>,
+        model: &dyn NavigationModel,
+    ) -> Result<Self, Overflow> {
+        let mut submarine = Submarine::default();
+        let steps = commands
+            .into_iter()
+            .map(|command| {
+                submarine = model.navigate(&submarine, &command)?;
+                Ok((command, submarine))
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self { steps })
+    }
+
+    /// Where the submarine ends up.
+    pub fn end(&self) -> Submarine {
+        self.steps
+            .last()
+            .map_or(Submarine::default(), |&(_, submarine)| submarine)
+    }
+
+    /// The submarine at the start and after each command.
+    fn submarines(&self) -> impl Iterator<Item = Submarine> + '_ {
+        std::iter::once(Submarine::default()).chain(self.steps.iter().map(|&(_, sub)| sub))
+    }
+}
+
+/// One row per step of each named trajectory, step 0 is the start.
+pub fn to_csv(trajectories: &[(&str, &Trajectory)]) -> String {
+    let mut csv = "model,step,command,distance,depth,aim,speed,heading,x,y\n".to_owned();
+    for (name, trajectory) in trajectories {
+        let commands =
+            std::iter::once(None).chain(trajectory.steps.iter().map(|(cmd, _)| Some(cmd)));
+        for (step, (command, sub)) in commands.zip(trajectory.submarines()).enumerate() {
+            let _ = writeln!(
+                csv,
+                "{},{},{},{},{},{},{},{},{},{}",
+                name,
+                step,
+                command.map_or(String::new(), Command::to_string),
+                sub.distance,
+                sub.depth,
+                sub.aim,
+                sub.speed,
+                sub.heading,
+                sub.x,
+                sub.y
+            );
+        }
+    }
+    csv
+}
+
+/// Draws the depth against the distance travelled for each named trajectory, on the same scale
+/// so they can be compared, with the surface as a grey line. For models that steer the distance
+/// is the length of the path however it turns, `plan_view` shows where it goes.
+pub fn depth_profile(trajectories: &[(&str, &Trajectory)]) -> String {
+    let points = || {
+        trajectories
+            .iter()
+            .flat_map(|(_, trajectory)| trajectory.submarines())
+            .map(|sub| (sub.distance as i64, sub.depth as i64))
+    };
+    let (min_x, max_x) = points().fold((0, 0), |(min, max), (x, _)| (x.min(min), x.max(max)));
+    let (min_y, max_y) = points().fold((0, 0), |(min, max), (_, y)| (y.min(min), y.max(max)));
+    let scale = |value: i64, min: i64, max: i64, size: u32| {
+        ((value - min) * (size as i64 - 1) / (max - min).max(1)) as u32
+    };
+    let point = |(x, y)| {
+        (
+            scale(x, min_x, max_x, PROFILE_WIDTH),
+            scale(y, min_y, max_y, PROFILE_HEIGHT),
+        )
+    };
+    let mut svg = Svg::new(PROFILE_WIDTH, PROFILE_HEIGHT);
+    let surface = point((0, 0)).1;
+    svg.line((0, surface), (PROFILE_WIDTH - 1, surface), "#ccc");
+    for (index, ((name, trajectory), colour)) in
+        trajectories.iter().zip(COLOURS.iter().cycle()).enumerate()
+    {
+        let path: Vec<_> = trajectory
+            .submarines()
+            .map(|sub| point((sub.distance as i64, sub.depth as i64)))
+            .collect();
+        svg.polyline(&path, colour);
+        svg.text(4, 16 * (index as u32 + 1), name, colour);
+    }
+    svg.to_string()
+}
+
+/// Draws each named trajectory from above, with x to the right and y up, on the same scale and
+/// with grey axes through the start. Only models that steer leave the x axis.
+pub fn plan_view(trajectories: &[(&str, &Trajectory)]) -> String {
+    let points = || {
+        trajectories
+            .iter()
+            .flat_map(|(_, trajectory)| trajectory.submarines())
+            .map(|sub| (sub.x, sub.y))
+    };
+    let (min_x, max_x) = points().fold((0.0, 0.0), |(min, max), (x, _)| (x.min(min), x.max(max)));
+    let (min_y, max_y) = points().fold((0.0, 0.0), |(min, max), (_, y)| (y.min(min), y.max(max)));
+    // a square around both ranges, centred on them
+    let span = (max_x - min_x).max(max_y - min_y).max(1.0);
+    let (left, top) = ((min_x + max_x - span) / 2.0, (min_y + max_y + span) / 2.0);
+    let size = (PLAN_SIZE - 1) as f64;
+    let point = |(x, y): (f64, f64)| {
+        (
+            ((x - left) / span * size).round() as u32,
+            ((top - y) / span * size).round() as u32,
+        )
+    };
+    let mut svg = Svg::new(PLAN_SIZE, PLAN_SIZE);
+    let origin = point((0.0, 0.0));
+    svg.line((0, origin.1), (PLAN_SIZE - 1, origin.1), "#ccc");
+    svg.line((origin.0, 0), (origin.0, PLAN_SIZE - 1), "#ccc");
+    for (index, ((name, trajectory), colour)) in
+        trajectories.iter().zip(COLOURS.iter().cycle()).enumerate()
+    {
+        let path: Vec<_> = trajectory
+            .submarines()
+            .map(|sub| point((sub.x, sub.y)))
+            .collect();
+        svg.polyline(&path, colour);
+        svg.text(4, 16 * (index as u32 + 1), name, colour);
+    }
+    svg.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::day2::models::{Aimed, Simple, Steered};
+    use crate::day2::script::compile;
+
+    static TEST_INPUT: &str = "forward 5
+down 5
+forward 8
+up 3
+down 8
+forward 2";
+
+    #[test]
+    fn test_record() {
+        let trajectory = Trajectory::record(compile(TEST_INPUT).unwrap(), &Aimed).unwrap();
+        assert_eq!(trajectory.steps.len(), 6);
+        assert_eq!(
+            trajectory.steps[2],
+            (
+                Command::Forward(8),
+                Submarine {
+                    distance: 13,
+                    depth: 40,
+                    aim: 5,
+                    ..Submarine::default()
+                }
+            )
+        );
+        assert_eq!(trajectory.end().position(), 900);
+        assert_eq!(Trajectory::default().end(), Submarine::default());
+        let commands = compile("down 2\nforward 2000000000").unwrap();
+        assert_eq!(Trajectory::record(commands, &Aimed), Err(Overflow));
+    }
+
+    #[test]
+    fn test_csv() {
+        let commands = compile("forward 2\ndown 1\nforward 3").unwrap();
+        let simple = Trajectory::record(commands.clone(), &Simple).unwrap();
+        let aimed = Trajectory::record(commands, &Aimed).unwrap();
+        assert_eq!(
+            to_csv(&[("simple", &simple), ("aimed", &aimed)]),
+            "model,step,command,distance,depth,aim,speed,heading,x,y
+simple,0,,0,0,0,0,0,0,0
+simple,1,forward 2,2,0,0,0,0,0,0
+simple,2,down 1,2,1,0,0,0,0,0
+simple,3,forward 3,5,1,0,0,0,0,0
+aimed,0,,0,0,0,0,0,0,0
+aimed,1,forward 2,2,0,0,0,0,0,0
+aimed,2,down 1,2,0,1,0,0,0,0
+aimed,3,forward 3,5,3,1,0,0,0,0
+"
+        );
+        let steered =
+            Trajectory::record(compile("forward 2\nleft 90\nback 3").unwrap(), &Steered).unwrap();
+        assert_eq!(
+            to_csv(&[("3d", &steered)]).lines().last(),
+            Some("3d,3,forward -3,5,0,0,0,90,2,-3")
+        );
+    }
+
+    #[test]
+    fn test_depth_profile() {
+        let trajectory =
+            Trajectory::record(compile("down 2\nforward 4\nup 4").unwrap(), &Simple).unwrap();
+        let svg = depth_profile(&[("simple", &trajectory)]);
+        assert!(svg.contains(r##"<line x1="0" y1="199" x2="799" y2="199" stroke="#ccc"/>"##));
+        assert!(svg.contains(
+            r#"<polyline points="0,199 0,399 799,399 799,0" fill="none" stroke="steelblue"/>"#
+        ));
+        assert!(svg.contains(r#"<text x="4" y="16" fill="steelblue">simple</text>"#));
+    }
+
+    #[test]
+    fn test_plan_view() {
+        let trajectory =
+            Trajectory::record(compile("forward 4\nleft 90\nforward 2").unwrap(), &Steered)
+                .unwrap();
+        let svg = plan_view(&[("3d", &trajectory)]);
+        assert!(svg.contains(r##"<line x1="0" y1="299" x2="399" y2="299" stroke="#ccc"/>"##));
+        assert!(svg.contains(
+            r#"<polyline points="0,299 399,299 399,299 399,100" fill="none" stroke="steelblue"/>"#
+        ));
+    }
+}
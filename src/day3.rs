@@ -1,6 +1,16 @@
+use crate::solvers::SolveError;
+use crate::utils::arith::{add, display, mul, Integer, Overflow};
+use crate::utils::cancel::CancelToken;
+use crate::utils::parse::{ParseError, ParseResult, Separator, Span};
 use crate::utils::parse_lines;
+use crate::utils::stream::LineSource;
+use std::io::{BufRead, Read};
 use std::str::FromStr;
 
+const BITS: usize = 12;
+/// The widths the solver is built for, the puzzle's numbers have 12 bits and the example's 5.
+const WIDTHS: [usize; 2] = [BITS, 5];
+
 #[derive(Debug, Copy, Clone, PartialEq)]
 enum MostCommonBit {
     Equal,
@@ -8,16 +18,19 @@ enum MostCommonBit {
     Zero,
 }
 
-fn find_most_common_bits<const N: usize>(lines: &Vec<Line<N>>) -> [MostCommonBit; N] {
+/// Only keeps a count per bit, so the lines can come from a stream.
+fn find_most_common_bits<const N: usize>(
+    lines: impl IntoIterator<Item = [bool; N]>,
+) -> [MostCommonBit; N] {
     let mut bits = [MostCommonBit::Equal; N];
     for (index, (zeros, ones)) in lines
-        .iter()
+        .into_iter()
         .fold([(0, 0); N], |mut zeros_and_ones, line| {
-            for index in 0..N {
-                if line.bits[index] {
-                    zeros_and_ones[index].1 += 1;
+            for (bit, counts) in line.iter().zip(zeros_and_ones.iter_mut()) {
+                if *bit {
+                    counts.1 += 1;
                 } else {
-                    zeros_and_ones[index].0 += 1;
+                    counts.0 += 1;
                 }
             }
             zeros_and_ones
@@ -59,71 +72,79 @@ struct Data<const N: usize> {
 
 impl<const N: usize> Data<N> {
     fn new(lines: Vec<Line<N>>) -> Self {
-        let bits = find_most_common_bits(&lines);
+        let bits = find_most_common_bits(lines.iter().map(|line| line.bits));
         Self { lines, bits }
     }
 
-    fn gamma(&self) -> usize {
-        bits_to_int(self.bits.iter().map(|bit| {
-            if *bit == MostCommonBit::Zero {
-                false
-            } else {
-                true
-            }
-        }))
-    }
-
-    fn epsilon(&self) -> usize {
-        bits_to_int(self.bits.iter().map(|bit| {
-            if *bit == MostCommonBit::One {
-                false
-            } else {
-                true
-            }
-        }))
-    }
-
-    fn power_consumption(&self) -> usize {
-        self.gamma() * self.epsilon()
+    fn power_consumption<I: Integer>(&self) -> Result<I, Overflow> {
+        power_consumption(&self.bits)
     }
 
-    fn oxygen_generator_rating(&self) -> usize {
+    fn oxygen_generator_rating<I: Integer>(&self) -> Result<I, SolveError> {
         let mut candidates = self.lines.clone();
         for index in 0..N {
-            candidates = match find_most_common_bits(&candidates)[index] {
+            candidates = match find_most_common_bits(candidates.iter().map(|line| line.bits))[index]
+            {
                 MostCommonBit::One | MostCommonBit::Equal => {
                     filter_candidates(candidates, index, true)
                 }
                 MostCommonBit::Zero => filter_candidates(candidates, index, false),
             };
             if candidates.len() == 1 {
-                return bits_to_int(candidates[0].bits);
+                return Ok(bits_to_int(candidates[0].bits)?);
             }
         }
-        unreachable!()
+        Err(ambiguous(candidates.len()))
     }
 
-    fn co2_scrubber_rating(&self) -> usize {
+    fn co2_scrubber_rating<I: Integer>(&self) -> Result<I, SolveError> {
         let mut candidates = self.lines.clone();
         for index in 0..N {
-            candidates = match find_most_common_bits(&candidates)[index] {
+            candidates = match find_most_common_bits(candidates.iter().map(|line| line.bits))[index]
+            {
                 MostCommonBit::One | MostCommonBit::Equal => {
                     filter_candidates(candidates, index, false)
                 }
                 MostCommonBit::Zero => filter_candidates(candidates, index, true),
             };
             if candidates.len() == 1 {
-                return bits_to_int(candidates[0].bits);
+                return Ok(bits_to_int(candidates[0].bits)?);
             }
         }
-        unreachable!()
+        Err(ambiguous(candidates.len()))
     }
 
-    fn life_support_rating(&self) -> usize {
-        self.oxygen_generator_rating() * self.co2_scrubber_rating()
+    fn life_support_rating<I: Integer>(&self) -> Result<I, SolveError> {
+        Ok(mul(
+            &self.oxygen_generator_rating()?,
+            &self.co2_scrubber_rating()?,
+        )?)
     }
 }
 
+/// Filtering on every bit leaves more than one line only if they are all the same number, the
+/// puzzle's ratings stop at a single line.
+fn ambiguous(remaining: usize) -> SolveError {
+    Span::new("")
+        .error(format!(
+            "a rating leaves {} lines of the same number, expected one",
+            remaining
+        ))
+        .into()
+}
+
+fn gamma<I: Integer, const N: usize>(bits: &[MostCommonBit; N]) -> Result<I, Overflow> {
+    bits_to_int(bits.iter().map(|bit| *bit != MostCommonBit::Zero))
+}
+
+fn epsilon<I: Integer, const N: usize>(bits: &[MostCommonBit; N]) -> Result<I, Overflow> {
+    bits_to_int(bits.iter().map(|bit| *bit != MostCommonBit::One))
+}
+
+fn power_consumption<I: Integer, const N: usize>(bits: &[MostCommonBit; N]) -> Result<I, Overflow> {
+    mul(&gamma(bits)?, &epsilon(bits)?)
+}
+
 fn filter_candidates<const N: usize>(
     candidates: Vec<Line<N>>,
     index: usize,
@@ -136,23 +157,139 @@ fn filter_candidates<const N: usize>(
         .collect()
 }
 
-fn bits_to_int<I: IntoIterator<Item = bool>>(bits: I) -> usize {
-    bits.into_iter()
-        .fold(0, |result, bit| (result << 1) ^ (bit as usize))
+fn bits_to_int<I: Integer, B: IntoIterator<Item = bool>>(bits: B) -> Result<I, Overflow> {
+    let two = add(&I::one(), &I::one())?;
+    bits.into_iter().try_fold(I::zero(), |result, bit| {
+        let shifted = mul(&result, &two)?;
+        if bit {
+            add(&shifted, &I::one())
+        } else {
+            Ok(shifted)
+        }
+    })
+}
+
+fn parse_bits<const N: usize>(span: Span) -> ParseResult<[bool; N]> {
+    let mut bits = [false; N];
+    let mut len = 0;
+    for (position, c) in span.chars() {
+        match (c, bits.get_mut(len)) {
+            ('0', Some(_)) => (),
+            ('1', Some(bit)) => *bit = true,
+            ('0' | '1', None) => (),
+            _ => return Err(position.error(format!("expected a bit, found {:?}", c))),
+        }
+        len += 1;
+    }
+    if len != N {
+        return Err(span.error(format!("expected {} bits, found {}", N, len)));
+    }
+    Ok(bits)
+}
+
+fn lint_lines(input: &str, width: usize) -> Vec<ParseError> {
+    let mut errors = Vec::new();
+    for line in Span::new(input).split(Separator::Lines) {
+        for (position, c) in line.chars() {
+            if c != '0' && c != '1' {
+                errors.push(position.error(format!("expected a bit, found {:?}", c)));
+            }
+        }
+        let len = line.as_str().chars().count();
+        if len != width {
+            errors.push(line.error(format!("expected {} bits, found {}", width, len)));
+        }
+    }
+    errors
+}
+
+/// The number of bits on the first line, which every other line should have too.
+fn width(input: &str) -> usize {
+    input
+        .lines()
+        .next()
+        .map_or(0, |line| line.trim().chars().count())
+}
+
+fn unsupported(width: usize) -> ParseError {
+    Span::new("").error(format!(
+        "expected numbers of {} or {} bits, found {}",
+        WIDTHS[0], WIDTHS[1], width
+    ))
+}
+
+pub fn lint(input: &str) -> Vec<ParseError> {
+    let width = width(input);
+    let mut errors = lint_lines(input, width);
+    if !WIDTHS.contains(&width) {
+        errors.insert(0, unsupported(width));
+    }
+    errors
+}
+
+fn power<const N: usize>(input: &str) -> Result<String, SolveError> {
+    let data = Data::<N>::new(parse_lines(input).collect());
+    Ok(data.power_consumption::<u64>()?.to_string())
+}
+
+fn life_support<const N: usize>(input: &str) -> Result<String, SolveError> {
+    let data = Data::<N>::new(parse_lines(input).collect());
+    Ok(data.life_support_rating::<u64>()?.to_string())
+}
+
+pub fn part1(input: &str, _cancel: &CancelToken) -> Result<String, SolveError> {
+    match width(input) {
+        BITS => power::<BITS>(input),
+        5 => power::<5>(input),
+        width => Err(unsupported(width).into()),
+    }
+}
+
+pub fn part2(input: &str, _cancel: &CancelToken) -> Result<String, SolveError> {
+    match width(input) {
+        BITS => life_support::<BITS>(input),
+        5 => life_support::<5>(input),
+        width => Err(unsupported(width).into()),
+    }
+}
+
+fn stream_power<const N: usize>(
+    reader: &mut dyn BufRead,
+    cancel: &CancelToken,
+) -> Result<String, SolveError> {
+    let lines = LineSource::new(reader, cancel).parse(parse_bits::<N>);
+    let bits = itertools::process_results(lines, |lines| find_most_common_bits(lines))?;
+    Ok(power_consumption::<u64, N>(&bits)?.to_string())
+}
+
+/// Power consumption only needs the bit counts, life support has to filter the lines so it
+/// cannot stream. The width is taken from the first line, which is then put back in front of the
+/// rest of the stream.
+pub fn stream_part1(reader: &mut dyn BufRead, cancel: &CancelToken) -> Result<String, SolveError> {
+    let mut first = String::new();
+    reader
+        .read_line(&mut first)
+        .map_err(|err| SolveError::Io(err.to_string()))?;
+    let mut reader = first.as_bytes().chain(reader);
+    match width(&first) {
+        BITS => stream_power::<BITS>(&mut reader, cancel),
+        5 => stream_power::<5>(&mut reader, cancel),
+        width => Err(unsupported(width).into()),
+    }
 }
 
 pub fn run() {
     let input = include_str!("data/3");
-    let data = Data::<12>::new(parse_lines(input).collect());
-    println!("{}", data.power_consumption());
-    println!("{}", data.life_support_rating());
+    let data = Data::<BITS>::new(parse_lines(input).collect());
+    println!("{}", display(data.power_consumption::<u64>()));
+    println!("{}", data.life_support_rating::<u64>().unwrap());
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    static TEST_INPUT: &'static str = "00100
+    static TEST_INPUT: &str = "00100
 11110
 10110
 10111
@@ -167,14 +304,15 @@ mod tests {
 
     #[test]
     fn test_bits_to_int() {
-        assert_eq!(bits_to_int([true, false, true, true, false]), 22);
+        assert_eq!(bits_to_int([true, false, true, true, false]), Ok(22u8));
+        assert_eq!(bits_to_int::<u8, _>([true; 9]), Err(Overflow));
     }
 
     #[test]
     fn test_power_consumption() {
         assert_eq!(
             Data::<5>::new(parse_lines(TEST_INPUT).collect()).power_consumption(),
-            198
+            Ok(198u64)
         );
     }
 
@@ -182,7 +320,73 @@ mod tests {
     fn test_life_support_rating() {
         assert_eq!(
             Data::<5>::new(parse_lines(TEST_INPUT).collect()).life_support_rating(),
-            230
+            Ok(230u64)
+        );
+    }
+
+    #[test]
+    fn test_parse_bits() {
+        assert_eq!(
+            parse_bits(Span::new("01101")),
+            Ok([false, true, true, false, true])
+        );
+        let err = parse_bits::<5>(Span::new("01201")).unwrap_err();
+        assert_eq!(err.column, 3);
+        let err = parse_bits::<5>(Span::new("011010")).unwrap_err();
+        assert_eq!(err.message, "expected 5 bits, found 6");
+    }
+
+    #[test]
+    fn test_stream() {
+        let cancel = CancelToken::new();
+        let lines = LineSource::new(TEST_INPUT.as_bytes(), &cancel).parse(parse_bits::<5>);
+        let bits = itertools::process_results(lines, |lines| find_most_common_bits(lines)).unwrap();
+        assert_eq!(power_consumption::<u64, 5>(&bits), Ok(198));
+    }
+
+    #[test]
+    fn test_width() {
+        let cancel = CancelToken::new();
+        assert_eq!(part1(TEST_INPUT, &cancel), Ok("198".to_owned()));
+        assert_eq!(part2(TEST_INPUT, &cancel), Ok("230".to_owned()));
+        let mut reader = TEST_INPUT.as_bytes();
+        assert_eq!(stream_part1(&mut reader, &cancel), Ok("198".to_owned()));
+        // a buffer that ends partway through the first line
+        let mut reader = std::io::BufReader::with_capacity(3, TEST_INPUT.as_bytes());
+        assert_eq!(stream_part1(&mut reader, &cancel), Ok("198".to_owned()));
+        let errors = lint("101\n010");
+        assert_eq!(errors.len(), 1);
+        assert_eq!(
+            errors[0].message,
+            "expected numbers of 12 or 5 bits, found 3"
+        );
+        assert!(part1("101\n010", &cancel).is_err());
+    }
+
+    #[test]
+    fn test_duplicates() {
+        let cancel = CancelToken::new();
+        let input = "10110\n10110\n01001";
+        assert!(lint(input).is_empty());
+        assert_eq!(part1(input, &cancel), Ok("198".to_owned()));
+        assert!(matches!(
+            part2(input, &cancel),
+            Err(SolveError::Parse(errors))
+                if errors[0].message == "a rating leaves 2 lines of the same number, expected one"
+        ));
+    }
+
+    #[test]
+    fn test_lint() {
+        assert!(lint(TEST_INPUT).is_empty());
+        assert!(lint_lines(TEST_INPUT, 5).is_empty());
+        let errors = lint_lines("00100\n0120\n111000", 5);
+        assert_eq!(
+            errors
+                .iter()
+                .map(|err| (err.line, err.column))
+                .collect::<Vec<_>>(),
+            vec![(2, 3), (2, 1), (3, 1)]
         );
     }
 }
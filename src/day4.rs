@@ -1,3 +1,9 @@
+use crate::animation::Simulation;
+use crate::solvers::SolveError;
+use crate::utils::cancel::CancelToken;
+use crate::utils::parse::{
+    self, array, integer, separated, ParseError, ParseResult, Separator, Span,
+};
 use itertools::Itertools;
 use std::str::FromStr;
 
@@ -38,6 +44,14 @@ impl Board {
     }
 }
 
+/// A board that got bingo, after how many draws and with which score.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Win {
+    board: usize,
+    draws: usize,
+    score: u32,
+}
+
 #[derive(Debug)]
 struct Bingo {
     order: Vec<u32>,
@@ -45,81 +59,186 @@ struct Bingo {
 }
 
 impl Bingo {
-    fn find_first_winner(&self) -> u32 {
-        for index in 5..self.order.len() {
-            let drawn = self.order.iter().take(index).collect_vec();
-            for board in &self.boards {
-                if board.check_win(&drawn) {
-                    return board.calculate_score(&drawn);
+    /// The boards in the order they win, boards winning on the same draw in board order.
+    fn winners(&self) -> Vec<Win> {
+        let mut winners: Vec<Win> = Vec::new();
+        for draws in 5..=self.order.len() {
+            let drawn = self.order.iter().take(draws).collect_vec();
+            for (index, board) in self.boards.iter().enumerate() {
+                if !winners.iter().any(|win| win.board == index) && board.check_win(&drawn) {
+                    winners.push(Win {
+                        board: index,
+                        draws,
+                        score: board.calculate_score(&drawn),
+                    });
                 }
             }
         }
-        unreachable!()
+        winners
     }
 
-    fn find_last_winner(&self) -> u32 {
-        let mut boards = self.boards.clone();
-        for index in 5..self.order.len() {
-            let drawn = self.order.iter().take(index).collect_vec();
-            if boards.len() == 1 {
-                let board = &boards[0];
-                if board.check_win(&drawn) {
-                    return board.calculate_score(&drawn);
-                }
-            } else {
-                boards = boards
-                    .into_iter()
-                    .filter(|board| !board.check_win(&drawn))
-                    .collect();
+    fn find_first_winner(&self) -> Result<u32, SolveError> {
+        Ok(self.winners().first().ok_or_else(no_winner)?.score)
+    }
+
+    fn find_last_winner(&self) -> Result<u32, SolveError> {
+        Ok(self.winners().last().ok_or_else(no_winner)?.score)
+    }
+}
+
+/// The numbers can run out before any board is complete, which lint cannot tell.
+fn no_winner() -> SolveError {
+    Span::new("")
+        .error("no board wins with the drawn numbers")
+        .into()
+}
+
+fn parse_board(span: Span) -> ParseResult<Board> {
+    let nums = array(Separator::Lines, array(Separator::Whitespace, integer))(span)?;
+    Ok(Board { nums })
+}
+
+impl FromStr for Bingo {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let span = Span::new(s);
+        let (order, boards) = match span.split(Separator::BlankLines).split_first() {
+            Some((order, boards)) => (*order, boards.to_vec()),
+            None => return Err(span.error("expected the drawn numbers")),
+        };
+        Ok(Self {
+            order: separated(Separator::Str(","), integer)(order)?,
+            boards: boards
+                .into_iter()
+                .map(parse_board)
+                .collect::<ParseResult<_>>()?,
+        })
+    }
+}
+
+pub fn lint(input: &str) -> Vec<ParseError> {
+    let span = Span::new(input);
+    let blocks = span.split(Separator::BlankLines);
+    let (order, boards) = match blocks.split_first() {
+        Some((order, boards)) => (order, boards),
+        None => return vec![span.error("expected the drawn numbers")],
+    };
+    let mut errors = parse::lint(*order, Separator::Str(","), integer::<u32>);
+    if boards.is_empty() {
+        errors.push(order.error("expected at least one board after the drawn numbers"));
+    }
+    for board in boards {
+        let rows = board.split(Separator::Lines);
+        if rows.len() != 5 {
+            errors.push(board.error(format!("expected 5 rows, found {}", rows.len())));
+        }
+        for row in rows {
+            if let Err(err) = array::<u32, 5>(Separator::Whitespace, integer)(row) {
+                errors.push(err);
             }
         }
-        unreachable!()
     }
+    errors
 }
 
-impl FromStr for Bingo {
-    type Err = ();
+#[derive(Debug)]
+pub struct BingoAnimation {
+    bingo: Bingo,
+    drawn: usize,
+}
+
+impl FromStr for BingoAnimation {
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut lines = s.lines().peekable();
-        let order = lines
-            .next()
-            .unwrap()
-            .split(',')
-            .map(|num| num.parse().unwrap())
-            .collect_vec();
-        let mut boards = Vec::new();
-        while lines.peek().is_some() {
-            lines.next();
-            let mut nums = [[0u32; 5]; 5];
-            for row_index in 0..5 {
-                for (col_index, num) in lines
-                    .next()
-                    .unwrap()
-                    .split_whitespace()
-                    .map(|num| num.parse().unwrap())
-                    .enumerate()
-                {
-                    nums[row_index][col_index] = num;
+        Ok(Self {
+            bingo: Bingo::from_str(s)?,
+            drawn: 0,
+        })
+    }
+}
+
+impl Simulation for BingoAnimation {
+    fn frame(&self) -> String {
+        let drawn = self.bingo.order.iter().take(self.drawn).collect_vec();
+        let mut frame = match drawn.last() {
+            Some(last) => format!(
+                "drew {} ({} of {})\n",
+                last,
+                self.drawn,
+                self.bingo.order.len()
+            ),
+            None => format!("nothing drawn yet (0 of {})\n", self.bingo.order.len()),
+        };
+        for (index, board) in self.bingo.boards.iter().enumerate() {
+            frame.push('\n');
+            if board.check_win(&drawn) {
+                frame.push_str(&format!("board {} wins\n", index + 1));
+            } else {
+                frame.push_str(&format!("board {}\n", index + 1));
+            }
+            for row in &board.nums {
+                for num in row {
+                    if drawn.contains(&num) {
+                        frame.push_str(&format!("[{:>2}]", num));
+                    } else {
+                        frame.push_str(&format!(" {:>2} ", num));
+                    }
                 }
+                frame.push('\n');
             }
-            boards.push(Board { nums })
         }
-        Ok(Self { order, boards })
+        frame
     }
+
+    fn step(&mut self) -> bool {
+        if self.drawn >= self.bingo.order.len() {
+            return false;
+        }
+        self.drawn += 1;
+        true
+    }
+}
+
+pub fn part1(input: &str, _cancel: &CancelToken) -> Result<String, SolveError> {
+    Ok(Bingo::from_str(input)?.find_first_winner()?.to_string())
+}
+
+pub fn part2(input: &str, _cancel: &CancelToken) -> Result<String, SolveError> {
+    Ok(Bingo::from_str(input)?.find_last_winner()?.to_string())
+}
+
+/// Every board in the order it wins, with the number that completed it and its score.
+pub fn trace(input: &str) -> Result<String, SolveError> {
+    let bingo = Bingo::from_str(input)?;
+    Ok(bingo
+        .winners()
+        .iter()
+        .map(|win| {
+            format!(
+                "board {:>3} wins after {:>2} draws on {:>2}, score {}\n",
+                win.board + 1,
+                win.draws,
+                bingo.order[win.draws - 1],
+                win.score
+            )
+        })
+        .collect::<String>())
 }
 
 pub fn run() {
     let bingo = Bingo::from_str(include_str!("data/4")).unwrap();
-    println!("{}", bingo.find_first_winner());
-    println!("{}", bingo.find_last_winner());
+    println!("{}", bingo.find_first_winner().unwrap());
+    println!("{}", bingo.find_last_winner().unwrap());
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::utils::snapshot::assert_snapshot;
 
-    static TEST_INPUT: &'static str =
+    static TEST_INPUT: &str =
         "7,4,9,5,11,17,23,2,0,14,21,24,10,16,13,6,15,25,12,22,18,20,8,19,3,26,1
 
 22 13 17 11  0
@@ -144,15 +263,64 @@ mod tests {
     fn test_find_first_winner() {
         assert_eq!(
             Bingo::from_str(TEST_INPUT).unwrap().find_first_winner(),
-            4512
-        )
+            Ok(4512)
+        );
+        // the numbers run out before any board is complete
+        let (_, boards) = TEST_INPUT.split_once("\n\n").unwrap();
+        let input = format!("7,4,9,5,11\n\n{}", boards);
+        assert!(lint(&input).is_empty());
+        let cancel = CancelToken::new();
+        for part in [part1, part2] {
+            assert!(matches!(
+                part(&input, &cancel),
+                Err(SolveError::Parse(errors))
+                    if errors[0].message == "no board wins with the drawn numbers"
+            ));
+        }
     }
 
     #[test]
     fn find_last_winner() {
         assert_eq!(
             Bingo::from_str(TEST_INPUT).unwrap().find_last_winner(),
-            1924
+            Ok(1924)
         )
     }
+
+    #[test]
+    fn test_winners_snapshot() {
+        assert_snapshot("day4_winners", &trace(include_str!("data/4")).unwrap());
+    }
+
+    #[test]
+    fn test_parse_error() {
+        let board = "1 2 3 4 5\n1 2 3 4 5 6\n1 2 3 4 5\n1 2 3 4 5\n1 2 3 4 5";
+        let err = Bingo::from_str(&format!("1,2,3\n\n{}", board)).unwrap_err();
+        assert_eq!((err.line, err.column), (4, 11));
+    }
+
+    #[test]
+    fn test_lint() {
+        assert!(lint(TEST_INPUT).is_empty());
+        let errors = lint("1,x,3\n\n1 2 3 4 5\n1 2 3 4\n1 2 3 4 5 6\n1 2 3 4 5");
+        assert_eq!(
+            errors
+                .iter()
+                .map(|err| (err.line, err.column))
+                .collect::<Vec<_>>(),
+            vec![(1, 3), (3, 1), (4, 8), (5, 11)]
+        );
+    }
+
+    #[test]
+    fn test_animation() {
+        let mut animation = BingoAnimation::from_str(TEST_INPUT).unwrap();
+        for _ in 0..12 {
+            animation.step();
+        }
+        let frame = animation.frame();
+        assert!(frame.starts_with("drew 24 (12 of 27)\n"));
+        assert!(frame.contains("board 3 wins\n[14][21][17][24][ 4]\n"));
+        assert!(frame.contains("board 1\n 22  13 [17][11][ 0]\n"));
+    }
 }
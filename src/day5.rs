@@ -1,4 +1,8 @@
+use crate::solvers::SolveError;
+use crate::utils::cancel::CancelToken;
+use crate::utils::parse::{self, integer, key_value, ParseError, ParseResult, Separator, Span};
 use crate::utils::parse_lines;
+use crate::utils::svg::Svg;
 use itertools::Itertools;
 use std::collections::HashMap;
 use std::str::FromStr;
@@ -19,6 +23,12 @@ impl Line {
         }
     }
 
+    fn ends(&self) -> (Point, Point) {
+        match self {
+            Line::Straight { start, end } | Line::Diagonal { start, end } => (*start, *end),
+        }
+    }
+
     fn is_straight(&self) -> bool {
         match self {
             Line::Straight { start, end } => true,
@@ -28,17 +38,21 @@ impl Line {
 }
 
 impl FromStr for Line {
-    type Err = ();
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let (start, end) = s.split_once(" -> ").unwrap();
-        let start = parse_coord(start);
-        let end = parse_coord(end);
-        if start.0 == end.0 || start.1 == end.1 {
-            Ok(Line::Straight { start, end })
-        } else {
-            Ok(Line::Diagonal { start, end })
-        }
+        parse_line(Span::new(s))
+    }
+}
+
+fn parse_line(span: Span) -> ParseResult<Line> {
+    let (start, end) = key_value(" -> ", parse_coord, parse_coord)(span)?;
+    if start.0 == end.0 || start.1 == end.1 {
+        Ok(Line::Straight { start, end })
+    } else if start.0.abs_diff(end.0) == start.1.abs_diff(end.1) {
+        Ok(Line::Diagonal { start, end })
+    } else {
+        Err(span.error("lines must be horizontal, vertical or at 45 degrees"))
     }
 }
 
@@ -75,9 +89,8 @@ fn diagonal_line(start: &Point, end: &Point) -> Vec<Point> {
     }
 }
 
-fn parse_coord(s: &str) -> (u32, u32) {
-    let (x, y) = s.split_once(',').unwrap();
-    (x.parse().unwrap(), y.parse().unwrap())
+fn parse_coord(span: Span) -> ParseResult<Point> {
+    key_value(",", integer, integer)(span)
 }
 
 fn danger_points(lines: &[Line]) -> HashMap<(u32, u32), u32> {
@@ -98,6 +111,71 @@ fn straights(lines: &[Line]) -> Vec<Line> {
         .collect_vec()
 }
 
+pub fn lint(input: &str) -> Vec<ParseError> {
+    parse::lint(Span::new(input), Separator::Lines, parse_line)
+}
+
+fn parse(input: &str) -> Result<Vec<Line>, SolveError> {
+    Ok(parse::separated(Separator::Lines, parse_line)(Span::new(
+        input,
+    ))?)
+}
+
+pub fn part1(input: &str, _cancel: &CancelToken) -> Result<String, SolveError> {
+    let points = danger_points(&straights(&parse(input)?));
+    Ok(points.values().filter(|&&n| n >= 2).count().to_string())
+}
+
+pub fn part2(input: &str, _cancel: &CancelToken) -> Result<String, SolveError> {
+    let points = danger_points(&parse(input)?);
+    Ok(points.values().filter(|&&n| n >= 2).count().to_string())
+}
+
+/// Draws the vent lines, with the points where at least two overlap in red.
+pub fn render(input: &str) -> Result<String, SolveError> {
+    let lines = parse(input)?;
+    let points = || {
+        lines.iter().flat_map(|line| {
+            let (start, end) = line.ends();
+            [start, end]
+        })
+    };
+    let width = points().map(|(x, _)| x + 1).max().unwrap_or(0);
+    let height = points().map(|(_, y)| y + 1).max().unwrap_or(0);
+    let mut svg = Svg::new(width, height);
+    for line in &lines {
+        let (start, end) = line.ends();
+        svg.line(start, end, "steelblue");
+    }
+    for (&(x, y), _) in danger_points(&lines)
+        .iter()
+        .filter(|(_, &n)| n >= 2)
+        .sorted()
+    {
+        svg.rect(x, y, 1, 1, "red");
+    }
+    Ok(svg.to_string())
+}
+
+/// Draws the vent lines as text, counting the lines through every point, `.` for none.
+pub fn grid(input: &str) -> Result<String, SolveError> {
+    let lines = parse(input)?;
+    let points = danger_points(&lines);
+    let width = points.keys().map(|(x, _)| x + 1).max().unwrap_or(0);
+    let height = points.keys().map(|(_, y)| y + 1).max().unwrap_or(0);
+    let mut grid = String::new();
+    for y in 0..height {
+        for x in 0..width {
+            match points.get(&(x, y)) {
+                Some(&n) => grid.push(char::from_digit(n.min(9), 10).unwrap()),
+                None => grid.push('.'),
+            }
+        }
+        grid.push('\n');
+    }
+    Ok(grid)
+}
+
 pub fn run() {
     let lines = parse_lines::<Line>(include_str!("data/5")).collect_vec();
     let points = danger_points(&straights(&lines));
@@ -110,7 +188,7 @@ pub fn run() {
 mod tests {
     use super::*;
 
-    static TEST_INPUT: &'static str = "0,9 -> 5,9
+    static TEST_INPUT: &str = "0,9 -> 5,9
 8,0 -> 0,8
 9,4 -> 3,4
 2,2 -> 2,1
@@ -141,4 +219,50 @@ mod tests {
             12
         );
     }
+
+    #[test]
+    fn test_render() {
+        let svg = render("0,0 -> 2,0\n1,0 -> 1,1").unwrap();
+        assert_eq!(
+            svg,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 3 2" width="3" height="2"><line x1="0" y1="0" x2="2" y2="0" stroke="steelblue"/><line x1="1" y1="0" x2="1" y2="1" stroke="steelblue"/><rect x="1" y="0" width="1" height="1" fill="red"/></svg>"#
+        );
+    }
+
+    #[test]
+    fn test_lint() {
+        assert!(lint(TEST_INPUT).is_empty());
+        let errors = lint("0,9 -> 5,9\n0,0 -> 2,1\n1,1 -> 2;2");
+        assert_eq!(
+            errors
+                .iter()
+                .map(|err| (err.line, err.column))
+                .collect::<Vec<_>>(),
+            vec![(2, 1), (3, 8)]
+        );
+    }
+
+    #[test]
+    fn test_parse_error() {
+        let err = Line::from_str("0,9 -> 5;9").unwrap_err();
+        assert_eq!((err.line, err.column), (1, 8));
+    }
+
+    #[test]
+    fn test_grid() {
+        assert_eq!(
+            grid(TEST_INPUT).unwrap(),
+            "1.1....11.
+.111...2..
+..2.1.111.
+...1.2.2..
+.112313211
+...1.2....
+..1...1...
+.1.....1..
+1.......1.
+222111....
+"
+        );
+    }
 }
@@ -1,75 +1,232 @@
+use crate::animation::Simulation;
+use crate::solvers::SolveError;
+use crate::utils::arith::{self, add, from_u64, Integer, Overflow};
+use crate::utils::cancel::CancelToken;
+use crate::utils::parse::{self, integer, separated, ParseError, ParseResult, Separator, Span};
 use itertools::Itertools;
 use std::str::FromStr;
 
 #[derive(Debug)]
-struct Population {
-    fish: [u64; 9],
+struct Population<N> {
+    fish: [N; 9],
 }
 
-impl Population {
-    fn new(one: u64, two: u64, three: u64, four: u64, five: u64, six: u64) -> Self {
-        Self {
-            fish: [0, one, two, three, four, five, six, 0, 0],
-        }
+impl<N: Integer> Population<N> {
+    /// The number of fish with each timer, from 0 to 8.
+    fn new(counts: [u64; 9]) -> Result<Self, Overflow> {
+        let fish: Vec<N> = counts
+            .iter()
+            .map(|&count| from_u64(count))
+            .collect::<Result<_, _>>()?;
+        Ok(Self {
+            fish: fish.try_into().expect("there are nine timers"),
+        })
     }
 
-    fn advance(&mut self) {
-        let zero = self.fish[0];
+    fn advance(&mut self) -> Result<(), Overflow> {
+        // calculate the new generation first, so an overflow leaves the population untouched
+        let six = add(&self.fish[7], &self.fish[0])?;
         self.fish.rotate_left(1);
-        self.fish[6] += zero;
+        self.fish[6] = six;
+        Ok(())
+    }
+
+    fn count(&self) -> Result<N, Overflow> {
+        arith::sum(self.fish.iter().cloned().map(Ok))
     }
+}
+
+fn simulate<N: Integer>(
+    population: &mut Population<N>,
+    days: usize,
+    cancel: &CancelToken,
+) -> Result<N, SolveError> {
+    for day in 0..days {
+        cancel.progress(day as u64, days as u64)?;
+        population.advance()?;
+    }
+    cancel.progress(days as u64, days as u64)?;
+    Ok(population.count()?)
+}
 
-    fn count(&self) -> u64 {
-        self.fish.iter().sum()
+/// An initial timer, the puzzle only starts fish from 1 to 6 but newborn fish count from 8.
+fn timer(span: Span) -> ParseResult<usize> {
+    match integer::<u8>(span)? {
+        timer @ 0..=8 => Ok(timer as usize),
+        timer => Err(span.trim().error(format!(
+            "initial timers must be between 0 and 8, found {}",
+            timer
+        ))),
     }
 }
 
-impl FromStr for Population {
-    type Err = ();
+impl<N: Integer> FromStr for Population<N> {
+    type Err = SolveError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let (one, two, three, four, five, six) = s.chars().fold(
-            (0, 0, 0, 0, 0, 0),
-            |(one, two, three, four, five, six), c| match c {
-                '1' => (one + 1, two, three, four, five, six),
-                '2' => (one, two + 1, three, four, five, six),
-                '3' => (one, two, three + 1, four, five, six),
-                '4' => (one, two, three, four + 1, five, six),
-                '5' => (one, two, three, four, five + 1, six),
-                '6' => (one, two, three, four, five, six + 1),
-                _ => (one, two, three, four, five, six),
-            },
-        );
-        Ok(Self::new(one, two, three, four, five, six))
+        let span = Span::new(s).trim();
+        if span.is_empty() {
+            return Err(span.error("expected at least one timer").into());
+        }
+        let mut counts = [0; 9];
+        for timer in separated(Separator::Str(","), timer)(span)? {
+            counts[timer] += 1;
+        }
+        Ok(Self::new(counts)?)
     }
 }
 
-pub fn run() {
-    let mut population = Population::from_str(include_str!("data/6")).unwrap();
-    for _ in 0..80 {
-        population.advance();
+pub fn lint(input: &str) -> Vec<ParseError> {
+    let span = Span::new(input).trim();
+    if span.is_empty() {
+        return vec![span.error("expected at least one timer")];
     }
-    println!("{}", population.count());
-    for _ in 0..176 {
-        population.advance();
+    parse::lint(span, Separator::Str(","), timer)
+}
+
+#[derive(Debug)]
+pub struct PopulationAnimation {
+    population: Population<u64>,
+    day: usize,
+    days: usize,
+}
+
+impl PopulationAnimation {
+    pub fn new(input: &str, days: usize) -> Result<Self, SolveError> {
+        Ok(Self {
+            population: Population::from_str(input)?,
+            day: 0,
+            days,
+        })
+    }
+}
+
+impl Simulation for PopulationAnimation {
+    fn frame(&self) -> String {
+        let largest = self
+            .population
+            .fish
+            .iter()
+            .max()
+            .cloned()
+            .unwrap_or(0)
+            .max(1);
+        let mut frame = match self.population.count() {
+            Ok(count) => format!("day {}: {} fish\n", self.day, count),
+            Err(err) => format!("day {}: {}\n", self.day, err),
+        };
+        for (timer, &count) in self.population.fish.iter().enumerate() {
+            let width = (count as f64 / largest as f64 * 50.0) as usize;
+            frame.push_str(&format!("{} {:<50} {}\n", timer, "#".repeat(width), count));
+        }
+        frame
+    }
+
+    fn step(&mut self) -> bool {
+        if self.day >= self.days || self.population.advance().is_err() {
+            return false;
+        }
+        self.day += 1;
+        true
+    }
+}
+
+pub fn part1(input: &str, cancel: &CancelToken) -> Result<String, SolveError> {
+    let mut population = Population::<u64>::from_str(input)?;
+    Ok(simulate(&mut population, 80, cancel)?.to_string())
+}
+
+pub fn part2(input: &str, cancel: &CancelToken) -> Result<String, SolveError> {
+    let mut population = Population::<u64>::from_str(input)?;
+    Ok(simulate(&mut population, 256, cancel)?.to_string())
+}
+
+pub fn run() {
+    let mut population = Population::<u64>::from_str(include_str!("data/6")).unwrap();
+    for days in [80, 176] {
+        match simulate(&mut population, days, &CancelToken::new()) {
+            Ok(count) => println!("{}", count),
+            Err(err) => println!("{}", err),
+        }
     }
-    println!("{}", population.count());
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use num_bigint::BigUint;
 
     #[test]
     fn test_population() {
-        let mut population = Population::from_str("3,4,3,1,2").unwrap();
-        for _ in 0..80 {
-            population.advance();
-        }
-        assert_eq!(population.count(), 5934);
-        for _ in 0..176 {
-            population.advance();
+        let cancel = CancelToken::new();
+        let mut population = Population::<u64>::from_str("3,4,3,1,2").unwrap();
+        assert_eq!(simulate(&mut population, 80, &cancel), Ok(5934));
+        assert_eq!(simulate(&mut population, 176, &cancel), Ok(26984457539));
+    }
+
+    #[test]
+    fn test_overflow() {
+        let cancel = CancelToken::new();
+        let mut population = Population::<u32>::from_str("3,4,3,1,2").unwrap();
+        assert_eq!(
+            simulate(&mut population, 256, &cancel),
+            Err(SolveError::Overflow)
+        );
+        let mut population = Population::<u128>::from_str("3,4,3,1,2").unwrap();
+        assert_eq!(
+            simulate(&mut population, 1024, &cancel),
+            Err(SolveError::Overflow)
+        );
+        let mut population = Population::<BigUint>::from_str("3,4,3,1,2").unwrap();
+        assert_eq!(
+            simulate(&mut population, 1024, &cancel).map(|count| count.to_string()),
+            Ok("3071431534644823031499861312415140620929".to_owned())
+        );
+    }
+
+    #[test]
+    fn test_cancel() {
+        let mut population = Population::<BigUint>::from_str("3,4,3,1,2").unwrap();
+        let cancel = CancelToken::new();
+        cancel.cancel();
+        assert_eq!(
+            simulate(&mut population, 1_000_000_000, &cancel),
+            Err(SolveError::Timeout)
+        );
+    }
+
+    #[test]
+    fn test_parse() {
+        let population = Population::<u64>::from_str("3,4,3,1,2\n").unwrap();
+        assert_eq!(population.fish, [0, 1, 1, 2, 1, 0, 0, 0, 0]);
+        let population = Population::<u64>::from_str("0,7,8").unwrap();
+        assert_eq!(population.fish, [1, 0, 0, 0, 0, 0, 0, 1, 1]);
+        for (input, column) in [("10", 1), ("3,9", 3), ("1,2,-1", 5), ("1;2", 1), ("", 1)] {
+            match Population::<u64>::from_str(input) {
+                Err(SolveError::Parse(errors)) => assert_eq!(errors[0].column, column, "{}", input),
+                other => panic!("{:?} parsed as {:?}", input, other),
+            }
         }
-        assert_eq!(population.count(), 26984457539);
+    }
+
+    #[test]
+    fn test_lint() {
+        assert!(lint("3,4,3,1,2\n").is_empty());
+        let errors = lint("3,9,x,18");
+        assert_eq!(
+            errors
+                .iter()
+                .map(|err| (err.line, err.column))
+                .collect::<Vec<_>>(),
+            vec![(1, 3), (1, 5), (1, 7)]
+        );
+    }
+
+    #[test]
+    fn test_animation() {
+        let mut animation = PopulationAnimation::new("3,4,3,1,2", 18).unwrap();
+        assert!(animation.frame().starts_with("day 0: 5 fish\n"));
+        while animation.step() {}
+        assert!(animation.frame().starts_with("day 18: 26 fish\n"));
     }
 }
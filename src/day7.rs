@@ -1,17 +1,19 @@
 #![feature(int_abs_diff)]
+use crate::solvers::SolveError;
+use crate::utils::arith::{self, from_u64, mul, Integer, Overflow};
+use crate::utils::cancel::CancelToken;
+use crate::utils::parse::{self, integer, separated, ParseError, Separator, Span};
 use itertools::Itertools;
 use std::collections::HashMap;
 use std::str::FromStr;
 
 fn abs_diff(lhs: u32, rhs: u32) -> u32 {
-    if lhs > rhs {
-        lhs - rhs
-    } else {
-        rhs - lhs
-    }
+    lhs.abs_diff(rhs)
 }
 
-fn to_fuel_cost(diff: u32) -> u32 {
+fn to_fuel_cost(diff: u32) -> u64 {
+    // the triangular number of a u32 always fits in a u64
+    let diff = diff as u64;
     (diff * (1 + diff)) / 2
 }
 
@@ -34,67 +36,170 @@ impl Crabs {
         }
     }
 
-    fn calculate_simple_cost(&self, to: u32) -> u32 {
-        self.positions
-            .iter()
-            .map(|(&position, &count)| abs_diff(position, to) * count)
-            .sum()
+    fn calculate_cost<N: Integer>(
+        &self,
+        to: u32,
+        cost: impl Fn(u32) -> u64,
+    ) -> Result<N, Overflow> {
+        arith::sum(self.positions.iter().map(|(&position, &count)| {
+            mul(
+                &from_u64(cost(abs_diff(position, to)))?,
+                &from_u64(count as u64)?,
+            )
+        }))
+    }
+
+    fn calculate_simple_cost<N: Integer>(&self, to: u32) -> Result<N, Overflow> {
+        self.calculate_cost(to, |diff| diff as u64)
     }
 
-    fn calculate_real_cost(&self, to: u32) -> u32 {
-        self.positions
-            .iter()
-            .map(|(&position, &count)| to_fuel_cost(abs_diff(position, to)) * count)
-            .sum()
+    fn calculate_real_cost<N: Integer>(&self, to: u32) -> Result<N, Overflow> {
+        self.calculate_cost(to, to_fuel_cost)
     }
 }
 
 impl FromStr for Crabs {
-    type Err = ();
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let span = Span::new(s).trim();
+        if span.is_empty() {
+            return Err(span.error("expected at least one crab position"));
+        }
         let mut positions = HashMap::new();
-        for num in s.split(',').map(|n| n.parse().unwrap()) {
+        for num in separated(Separator::Str(","), integer)(span)? {
             *positions.entry(num).or_insert(0) += 1;
         }
         Ok(Self::new(positions))
     }
 }
 
-fn ideal_simple_fuel_consumption(crabs: &Crabs) -> u32 {
-    (crabs.min..=crabs.max)
-        .map(|position| crabs.calculate_simple_cost(position))
-        .min()
-        .unwrap()
+fn ideal_fuel_consumption<N: Integer>(
+    crabs: &Crabs,
+    cost: impl Fn(u32) -> Result<N, Overflow>,
+    cancel: &CancelToken,
+) -> Result<N, SolveError> {
+    let total = (crabs.max - crabs.min) as u64 + 1;
+    itertools::process_results(
+        (crabs.min..=crabs.max).map(|position| {
+            cancel.progress((position - crabs.min) as u64, total)?;
+            Ok(cost(position)?)
+        }),
+        |costs| costs.min(),
+    )
+    .map(|cost| cost.expect("there is at least one crab"))
+}
+
+fn ideal_simple_fuel_consumption<N: Integer>(
+    crabs: &Crabs,
+    cancel: &CancelToken,
+) -> Result<N, SolveError> {
+    ideal_fuel_consumption(
+        crabs,
+        |position| crabs.calculate_simple_cost(position),
+        cancel,
+    )
+}
+
+fn ideal_real_fuel_consumption<N: Integer>(
+    crabs: &Crabs,
+    cancel: &CancelToken,
+) -> Result<N, SolveError> {
+    ideal_fuel_consumption(
+        crabs,
+        |position| crabs.calculate_real_cost(position),
+        cancel,
+    )
+}
+
+pub fn lint(input: &str) -> Vec<ParseError> {
+    let span = Span::new(input).trim();
+    if span.is_empty() {
+        return vec![span.error("expected at least one crab position")];
+    }
+    parse::lint(span, Separator::Str(","), integer::<u32>)
+}
+
+pub fn part1(input: &str, cancel: &CancelToken) -> Result<String, SolveError> {
+    let crabs = Crabs::from_str(input)?;
+    Ok(ideal_simple_fuel_consumption::<u64>(&crabs, cancel)?.to_string())
 }
 
-fn ideal_real_fuel_consumption(crabs: &Crabs) -> u32 {
-    (crabs.min..=crabs.max)
-        .map(|position| crabs.calculate_real_cost(position))
-        .min()
-        .unwrap()
+pub fn part2(input: &str, cancel: &CancelToken) -> Result<String, SolveError> {
+    let crabs = Crabs::from_str(input)?;
+    Ok(ideal_real_fuel_consumption::<u64>(&crabs, cancel)?.to_string())
 }
 
 pub fn run() {
     let crabs = Crabs::from_str(include_str!("data/7")).unwrap();
-    println!("{}", ideal_simple_fuel_consumption(&crabs));
-    println!("{}", ideal_real_fuel_consumption(&crabs));
+    let cancel = CancelToken::new();
+    for result in [
+        ideal_simple_fuel_consumption::<u64>(&crabs, &cancel),
+        ideal_real_fuel_consumption::<u64>(&crabs, &cancel),
+    ] {
+        match result {
+            Ok(cost) => println!("{}", cost),
+            Err(err) => println!("{}", err),
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use num_bigint::BigUint;
+    use std::time::Duration;
 
-    static TEST_INPUT: &'static str = "16,1,2,0,4,2,7,1,2,14";
+    static TEST_INPUT: &str = "16,1,2,0,4,2,7,1,2,14";
 
     #[test]
     fn test_ideal_simple_fuel_consumption() {
         let crabs = Crabs::from_str(TEST_INPUT).unwrap();
-        assert_eq!(ideal_simple_fuel_consumption(&crabs), 37)
+        assert_eq!(
+            ideal_simple_fuel_consumption::<u64>(&crabs, &CancelToken::new()),
+            Ok(37)
+        )
     }
     #[test]
     fn test_ideal_real_fuel_consumption() {
         let crabs = Crabs::from_str(TEST_INPUT).unwrap();
-        assert_eq!(ideal_real_fuel_consumption(&crabs), 168)
+        assert_eq!(
+            ideal_real_fuel_consumption::<u64>(&crabs, &CancelToken::new()),
+            Ok(168)
+        )
+    }
+
+    #[test]
+    fn test_overflow() {
+        let crabs = Crabs::from_str("0,100000,100000").unwrap();
+        assert_eq!(
+            ideal_real_fuel_consumption::<u32>(&crabs, &CancelToken::new()),
+            Err(SolveError::Overflow)
+        );
+        assert_eq!(
+            ideal_real_fuel_consumption::<u64>(&crabs, &CancelToken::new()),
+            Ok(3333400000)
+        );
+        assert_eq!(
+            ideal_real_fuel_consumption::<BigUint>(&crabs, &CancelToken::new()),
+            Ok(BigUint::from(3333400000u64))
+        );
+    }
+
+    #[test]
+    fn test_timeout() {
+        let crabs = Crabs::from_str("0,4000000000").unwrap();
+        let cancel = CancelToken::with_timeout(Duration::from_millis(10));
+        assert_eq!(
+            ideal_simple_fuel_consumption::<u64>(&crabs, &cancel),
+            Err(SolveError::Timeout)
+        );
+    }
+
+    #[test]
+    fn test_lint() {
+        assert!(lint(TEST_INPUT).is_empty());
+        assert_eq!(lint("").len(), 1);
+        assert_eq!(lint("1,-2,3")[0].column, 3);
     }
 }
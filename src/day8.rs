@@ -1,9 +1,12 @@
+use crate::solvers::SolveError;
+use crate::utils::cancel::CancelToken;
+use crate::utils::parse::{self, array, key_value, ParseError, ParseResult, Separator, Span};
 use crate::utils::parse_lines;
 use itertools::Itertools;
 use std::collections::HashSet;
 use std::str::FromStr;
 
-#[derive(Debug, Hash, Eq, PartialEq, Copy, Clone)]
+#[derive(Debug, Hash, Eq, PartialEq, Ord, PartialOrd, Copy, Clone)]
 enum Wire {
     A,
     B,
@@ -14,169 +17,210 @@ enum Wire {
     G,
 }
 
-impl From<char> for Wire {
-    fn from(c: char) -> Self {
+impl TryFrom<char> for Wire {
+    type Error = ();
+
+    fn try_from(c: char) -> Result<Self, Self::Error> {
         match c {
-            'a' => Self::A,
-            'b' => Self::B,
-            'c' => Self::C,
-            'd' => Self::D,
-            'e' => Self::E,
-            'f' => Self::F,
-            'g' => Self::G,
-            _ => panic!("Unexpected wire {}", c),
+            'a' => Ok(Self::A),
+            'b' => Ok(Self::B),
+            'c' => Ok(Self::C),
+            'd' => Ok(Self::D),
+            'e' => Ok(Self::E),
+            'f' => Ok(Self::F),
+            'g' => Ok(Self::G),
+            _ => Err(()),
         }
     }
 }
 
 #[derive(Debug)]
 struct Entry {
-    patterns: [HashSet<Wire>; 10],
-    digits: [HashSet<Wire>; 4],
+    /// The patterns in digit order, the first one lights up a zero.
+    wiring: [HashSet<Wire>; 10],
+    digits: [u8; 4],
 }
 
 impl Entry {
-    fn decoded_digits(&self) -> [u8; 4] {
-        // find the one, four, seven and eight patterns.
-        let (one, four, seven, eight) = self.patterns.iter().fold(
-            (
-                HashSet::new(),
-                HashSet::new(),
-                HashSet::new(),
-                HashSet::new(),
-            ),
-            |(one, four, seven, eight), pat| match pat.len() {
-                2 => (pat.clone(), four, seven, eight),
-                3 => (one, four, pat.clone(), eight),
-                4 => (one, pat.clone(), seven, eight),
-                7 => (one, four, seven, pat.clone()),
-                _ => (one, four, seven, eight),
-            },
-        );
-        // find the candidates for two, three and five
-        let two_three_or_five = self
-            .patterns
-            .iter()
-            .filter(|pat| pat.len() == 5)
-            .cloned()
-            .collect_vec();
-        // find the candidates for zero, six and nine
-        let zero_six_or_nine = self
-            .patterns
-            .iter()
-            .filter(|pat| pat.len() == 6)
-            .cloned()
-            .collect_vec();
-        // find the "d" wire using the intersection of the two, three, five candidates
-        // then intersecting that with the difference between four and one
-        let d = two_three_or_five
-            .iter()
-            .cloned()
-            .reduce(|a, b| a.intersection(&b).cloned().collect())
-            .unwrap()
-            .intersection(&four.difference(&one).cloned().collect())
-            .cloned()
-            .next()
-            .unwrap();
-        // find the "a" wire using the difference between seven and one
-        let a = seven.difference(&one).next().unwrap().clone();
-        // find the "b" wire from the difference between four and one that is not the "d" wire
-        let b = four
-            .difference(&one)
-            .filter(|&wire| wire != &d)
-            .next()
-            .unwrap()
-            .clone();
-        // find zero from the candidates where the "d" wire is not set
-        let zero = zero_six_or_nine
-            .iter()
-            .find(|pat| !pat.contains(&d))
-            .unwrap()
-            .clone();
-        // find nine from the candidates by checking if it shares all the wires from one and "d"
-        let nine = zero_six_or_nine
-            .iter()
-            .find(|&pat| one.iter().all(|wire| pat.contains(wire)) && pat.contains(&d))
-            .unwrap()
-            .clone();
-        // six is the last candidate from that group
-        let six = zero_six_or_nine
-            .iter()
-            .find(|&pat| pat != &zero && pat != &nine)
-            .unwrap()
-            .clone();
-        // find three from candidates by checking if it shares all the wires from one
-        let three = two_three_or_five
-            .iter()
-            .find(|&pat| one.iter().all(|wire| pat.contains(wire)))
-            .unwrap()
-            .clone();
-        // find five from the candidates by checking if it contains the "b" wire.
-        let five = two_three_or_five
-            .iter()
-            .find(|&pat| pat.contains(&b))
-            .unwrap()
-            .clone();
-        // two is the remaining candidate
-        let two = two_three_or_five
-            .iter()
-            .find(|&pat| pat != &three && pat != &five)
-            .unwrap()
-            .clone();
-        let mut numbers = [0; 4];
-        for (index, digit) in self.digits.iter().enumerate() {
-            if digit == &zero {
-                numbers[index] = 0;
-            } else if digit == &one {
-                numbers[index] = 1;
-            } else if digit == &two {
-                numbers[index] = 2;
-            } else if digit == &three {
-                numbers[index] = 3;
-            } else if digit == &four {
-                numbers[index] = 4;
-            } else if digit == &five {
-                numbers[index] = 5;
-            } else if digit == &six {
-                numbers[index] = 6;
-            } else if digit == &seven {
-                numbers[index] = 7;
-            } else if digit == &eight {
-                numbers[index] = 8;
-            } else if digit == &nine {
-                numbers[index] = 9;
-            }
-        }
-        numbers
-    }
-
     fn digit(&self) -> u32 {
-        let decoded = self.decoded_digits();
-        (decoded[0] as u32 * 1000)
-            + (decoded[1] as u32 * 100)
-            + (decoded[2] as u32 * 10)
-            + (decoded[3] as u32)
+        (self.digits[0] as u32 * 1000)
+            + (self.digits[1] as u32 * 100)
+            + (self.digits[2] as u32 * 10)
+            + (self.digits[3] as u32)
     }
 }
 
+/// The patterns in digit order, None if they do not light up the ten digits.
+fn wiring(patterns: &[HashSet<Wire>; 10]) -> Option<[HashSet<Wire>; 10]> {
+    // find the one, four, seven and eight patterns.
+    let (one, four, seven, eight) = patterns.iter().fold(
+        (
+            HashSet::new(),
+            HashSet::new(),
+            HashSet::new(),
+            HashSet::new(),
+        ),
+        |(one, four, seven, eight), pat| match pat.len() {
+            2 => (pat.clone(), four, seven, eight),
+            3 => (one, four, pat.clone(), eight),
+            4 => (one, pat.clone(), seven, eight),
+            7 => (one, four, seven, pat.clone()),
+            _ => (one, four, seven, eight),
+        },
+    );
+    // find the candidates for two, three and five
+    let two_three_or_five = patterns
+        .iter()
+        .filter(|pat| pat.len() == 5)
+        .cloned()
+        .collect_vec();
+    // find the candidates for zero, six and nine
+    let zero_six_or_nine = patterns
+        .iter()
+        .filter(|pat| pat.len() == 6)
+        .cloned()
+        .collect_vec();
+    // find the "d" wire using the intersection of the two, three, five candidates
+    // then intersecting that with the difference between four and one
+    let d = two_three_or_five
+        .iter()
+        .cloned()
+        .reduce(|a, b| a.intersection(&b).cloned().collect())?
+        .intersection(&four.difference(&one).cloned().collect())
+        .cloned()
+        .next()?;
+    // find the "a" wire using the difference between seven and one
+    let a = *seven.difference(&one).next()?;
+    // find the "b" wire from the difference between four and one that is not the "d" wire
+    let b = *four.difference(&one).find(|&wire| wire != &d)?;
+    // find zero from the candidates where the "d" wire is not set
+    let zero = zero_six_or_nine
+        .iter()
+        .find(|pat| !pat.contains(&d))?
+        .clone();
+    // find nine from the candidates by checking if it shares all the wires from one and "d"
+    let nine = zero_six_or_nine
+        .iter()
+        .find(|&pat| one.iter().all(|wire| pat.contains(wire)) && pat.contains(&d))?
+        .clone();
+    // six is the last candidate from that group
+    let six = zero_six_or_nine
+        .iter()
+        .find(|&pat| pat != &zero && pat != &nine)?
+        .clone();
+    // find three from candidates by checking if it shares all the wires from one
+    let three = two_three_or_five
+        .iter()
+        .find(|&pat| one.iter().all(|wire| pat.contains(wire)))?
+        .clone();
+    // find five from the candidates by checking if it contains the "b" wire.
+    let five = two_three_or_five
+        .iter()
+        .find(|&pat| pat.contains(&b))?
+        .clone();
+    // two is the remaining candidate
+    let two = two_three_or_five
+        .iter()
+        .find(|&pat| pat != &three && pat != &five)?
+        .clone();
+    let wiring = [zero, one, two, three, four, five, six, seven, eight, nine];
+    // every pattern has to be used exactly once, or the deductions above were thrown off
+    let distinct = wiring
+        .iter()
+        .tuple_combinations()
+        .all(|(first, second)| first != second);
+    (distinct && patterns.iter().all(|pattern| wiring.contains(pattern))).then_some(wiring)
+}
+
 impl FromStr for Entry {
-    type Err = ();
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let (patterns, digits) = s.split_once(" | ").ok_or(())?;
-        Ok(Self {
-            patterns: decode_wirings(patterns),
-            digits: decode_wirings(digits),
-        })
+        parse_entry(Span::new(s))
     }
 }
 
-fn decode_wirings<const N: usize>(s: &str) -> [HashSet<Wire>; N] {
-    let mut wirings = [(); N].map(|_| HashSet::new());
-    for (index, pattern) in s.split(" ").enumerate() {
-        wirings[index] = pattern.chars().map(|c| Wire::from(c)).collect();
+fn parse_entry(span: Span) -> ParseResult<Entry> {
+    let digit = |span| Ok((span, decode_wiring(span)?));
+    let (patterns, digits): (_, [_; 4]) =
+        key_value(" | ", decode_wirings, array(Separator::Whitespace, digit))(span)?;
+    let wiring = wiring(&patterns)
+        .ok_or_else(|| span.error("the patterns do not light up the ten digits"))?;
+    let mut decoded = [0; 4];
+    for (value, (span, wires)) in decoded.iter_mut().zip(&digits) {
+        *value = wiring
+            .iter()
+            .position(|pattern| pattern == wires)
+            .ok_or_else(|| span.error("the digit matches none of the patterns"))?
+            as u8;
     }
-    wirings
+    Ok(Entry {
+        wiring,
+        digits: decoded,
+    })
+}
+
+fn decode_wirings<const N: usize>(span: Span) -> ParseResult<[HashSet<Wire>; N]> {
+    array(Separator::Whitespace, decode_wiring)(span)
+}
+
+fn decode_wiring(span: Span) -> ParseResult<HashSet<Wire>> {
+    span.chars()
+        .map(|(position, c)| {
+            Wire::try_from(c).map_err(|_| position.error(format!("unexpected wire {:?}", c)))
+        })
+        .collect()
+}
+
+pub fn lint(input: &str) -> Vec<ParseError> {
+    parse::lint(Span::new(input), Separator::Lines, parse_entry)
+}
+
+fn parse(input: &str) -> Result<Vec<Entry>, SolveError> {
+    Ok(parse::separated(Separator::Lines, parse_entry)(Span::new(
+        input,
+    ))?)
+}
+
+pub fn part1(input: &str, _cancel: &CancelToken) -> Result<String, SolveError> {
+    Ok(parse(input)?
+        .iter()
+        .flat_map(|entry| entry.digits)
+        .filter(|&digit| digit == 1 || digit == 4 || digit == 7 || digit == 8)
+        .count()
+        .to_string())
+}
+
+pub fn part2(input: &str, _cancel: &CancelToken) -> Result<String, SolveError> {
+    Ok(parse(input)?
+        .iter()
+        .map(|entry| entry.digit())
+        .sum::<u32>()
+        .to_string())
+}
+
+/// The wiring worked out for every entry, as the wires of each digit, and the decoded value.
+pub fn trace(input: &str) -> Result<String, SolveError> {
+    Ok(parse(input)?
+        .iter()
+        .enumerate()
+        .map(|(index, entry)| {
+            let wiring = entry
+                .wiring
+                .iter()
+                .enumerate()
+                .map(|(digit, pattern)| {
+                    let wires = pattern
+                        .iter()
+                        .sorted()
+                        .map(|wire| format!("{:?}", wire).to_lowercase())
+                        .join("");
+                    format!("{}={}", digit, wires)
+                })
+                .join(" ");
+            format!("{:>3}: {} -> {}\n", index + 1, wiring, entry.digit())
+        })
+        .collect::<String>())
 }
 
 pub fn run() {
@@ -184,7 +228,7 @@ pub fn run() {
     println!(
         "{}",
         parse_lines::<Entry>(input)
-            .flat_map(|entry| entry.decoded_digits())
+            .flat_map(|entry| entry.digits)
             .filter(|&digit| digit == 1 || digit == 4 || digit == 7 || digit == 8)
             .count()
     );
@@ -199,6 +243,7 @@ pub fn run() {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::utils::snapshot::assert_snapshot;
 
     #[test]
     fn test_simple_digits() {
@@ -215,13 +260,79 @@ egadfb cdbfeg cegd fecab cgb gbdefca cg fgcdab egfdb bfceg | gbdfcae bgc cg cgb
 gcafb gcf dcaebfg ecagb gf abcdeg gaef cafbge fdbac fegbdc | fgae cfgab fg bagce";
         assert_eq!(
             parse_lines::<Entry>(input)
-                .flat_map(|entry| entry.decoded_digits())
+                .flat_map(|entry| entry.digits)
                 .filter(|&digit| digit == 1 || digit == 4 || digit == 7 || digit == 8)
                 .count(),
             26
         );
     }
 
+    #[test]
+    fn test_parse_error() {
+        let err =
+            Entry::from_str("ab abc abcd abcde abcdef abcdefg b c d e | ab ah c d").unwrap_err();
+        assert_eq!(
+            (err.line, err.column, err.message.as_str()),
+            (1, 48, "unexpected wire 'h'")
+        );
+        let err =
+            Entry::from_str("ab abc abcd abcde abcdef abcdefg b c d e | a b c d e").unwrap_err();
+        assert_eq!((err.line, err.column), (1, 52));
+    }
+
+    #[test]
+    fn test_lint() {
+        let entry =
+            "acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb ab | cdfeb fcadb cdfeb cdbaf";
+        assert!(lint(entry).is_empty());
+        let errors = lint(&format!(
+            "{}\nab | cd\nab abc abcd abcde abcdef abcdefg b c d e | ab a c d",
+            entry
+        ));
+        assert_eq!(
+            errors
+                .iter()
+                .map(|err| (err.line, err.column))
+                .collect::<Vec<_>>(),
+            vec![(2, 3), (3, 1)]
+        );
+        assert_eq!(
+            errors[1].message,
+            "the patterns do not light up the ten digits"
+        );
+    }
+
+    #[test]
+    fn test_wiring_errors() {
+        // a second one in place of the eight
+        let err = Entry::from_str(
+            "acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb ab | cdfeb fcadb cdfeb cdbaf"
+                .replace("acedgfb", "ac")
+                .as_str(),
+        )
+        .unwrap_err();
+        assert_eq!((err.line, err.column), (1, 1));
+        // the same wires in another order are the same digit, missing one is not
+        let entry = Entry::from_str(
+            "acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb ab | cdfeb fcadb cdfeb bfcda",
+        )
+        .unwrap();
+        assert_eq!(entry.digit(), 5353);
+        let err = Entry::from_str(
+            "acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb ab | cdfeb fcadb cdfe cdbaf",
+        )
+        .unwrap_err();
+        assert_eq!(
+            (err.line, err.column, err.message.as_str()),
+            (1, 74, "the digit matches none of the patterns")
+        );
+    }
+
+    #[test]
+    fn test_wiring_snapshot() {
+        assert_snapshot("day8_wiring", &trace(include_str!("data/8")).unwrap());
+    }
+
     #[test]
     fn test_sum() {
         let input =
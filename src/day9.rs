@@ -1,3 +1,8 @@
+use crate::animation::Simulation;
+use crate::solvers::SolveError;
+use crate::utils::cancel::CancelToken;
+use crate::utils::parse::{ParseError, Separator, Span};
+use crate::utils::svg::Svg;
 use crate::utils::{direct_neighbour_values, direct_neighbours};
 use itertools::Itertools;
 use std::collections::{HashMap, HashSet};
@@ -9,6 +14,12 @@ struct Heightmap {
 }
 
 impl Heightmap {
+    fn size(&self) -> (usize, usize) {
+        self.points.keys().fold((0, 0), |(width, height), &(x, y)| {
+            (width.max(x + 1), height.max(y + 1))
+        })
+    }
+
     fn low_points(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
         self.points.iter().flat_map(|(pos, height)| {
             if direct_neighbour_values(pos, &self.points)
@@ -26,20 +37,9 @@ impl Heightmap {
     }
 
     fn basin(&self, pos: &(usize, usize)) -> HashSet<(usize, usize)> {
-        let mut basin = HashSet::new();
-        let mut search_queue = vec![*pos];
-        while let Some(pos) = search_queue.pop() {
-            for neighbour in direct_neighbours(&pos).flat_map(|pos| {
-                self.points
-                    .get(&pos)
-                    .and_then(|height| if *height < 9 { Some(pos) } else { None })
-            }) {
-                if basin.insert(neighbour.clone()) {
-                    search_queue.push(neighbour);
-                }
-            }
-        }
-        basin
+        let mut fill = FloodFill::new(*pos);
+        while fill.step(self) {}
+        fill.basin
     }
 
     fn three_largest_basin_size(&self) -> usize {
@@ -48,7 +48,7 @@ impl Heightmap {
             .sorted()
             .rev()
             .take(3)
-            .fold(1, |acc, size| acc * size)
+            .product()
     }
 
     fn risk_level(&self) -> u32 {
@@ -58,21 +58,219 @@ impl Heightmap {
     }
 }
 
+#[derive(Debug)]
+struct FloodFill {
+    basin: HashSet<(usize, usize)>,
+    search_queue: Vec<(usize, usize)>,
+}
+
+impl FloodFill {
+    fn new(pos: (usize, usize)) -> Self {
+        Self {
+            basin: HashSet::new(),
+            search_queue: vec![pos],
+        }
+    }
+
+    /// Visits the next position in the search queue, returns false once the basin is complete.
+    fn step(&mut self, heightmap: &Heightmap) -> bool {
+        let pos = match self.search_queue.pop() {
+            Some(pos) => pos,
+            None => return false,
+        };
+        for neighbour in direct_neighbours(&pos).flat_map(|pos| {
+            heightmap
+                .points
+                .get(&pos)
+                .and_then(|height| if *height < 9 { Some(pos) } else { None })
+        }) {
+            if self.basin.insert(neighbour) {
+                self.search_queue.push(neighbour);
+            }
+        }
+        true
+    }
+}
+
 impl FromStr for Heightmap {
-    type Err = ();
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        Ok(Heightmap {
-            points: HashMap::from_iter(s.lines().enumerate().flat_map(|(y, line)| {
-                line.chars()
-                    .map(|c| c.to_digit(10).unwrap())
-                    .enumerate()
-                    .map(move |(x, height)| ((x, y), height))
-            })),
+        let mut points = HashMap::new();
+        for (y, line) in Span::new(s).split(Separator::Lines).into_iter().enumerate() {
+            for (x, (position, c)) in line.chars().enumerate() {
+                let height = c
+                    .to_digit(10)
+                    .ok_or_else(|| position.error(format!("expected a height, found {:?}", c)))?;
+                points.insert((x, y), height);
+            }
+        }
+        Ok(Heightmap { points })
+    }
+}
+
+pub fn lint(input: &str) -> Vec<ParseError> {
+    let mut errors = Vec::new();
+    let mut width = None;
+    for line in Span::new(input).split(Separator::Lines) {
+        for (position, c) in line.chars() {
+            if !c.is_ascii_digit() {
+                errors.push(position.error(format!("expected a height, found {:?}", c)));
+            }
+        }
+        let len = line.as_str().chars().count();
+        match width {
+            None => width = Some(len),
+            Some(width) if width != len => errors.push(line.error(format!(
+                "expected {} heights like the first row, found {}",
+                width, len
+            ))),
+            _ => (),
+        }
+    }
+    errors
+}
+
+#[derive(Debug)]
+pub struct BasinAnimation {
+    heightmap: Heightmap,
+    low_points: Vec<(usize, usize)>,
+    filled: HashSet<(usize, usize)>,
+    fill: Option<FloodFill>,
+}
+
+impl FromStr for BasinAnimation {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let heightmap = Heightmap::from_str(s)?;
+        // fill the basins top to bottom, the order of the hash map is not stable
+        let low_points = heightmap
+            .low_points()
+            .sorted_by_key(|&(x, y)| (std::cmp::Reverse(y), std::cmp::Reverse(x)))
+            .collect();
+        Ok(Self {
+            heightmap,
+            low_points,
+            filled: HashSet::new(),
+            fill: None,
         })
     }
 }
 
+impl Simulation for BasinAnimation {
+    fn frame(&self) -> String {
+        let (width, height) = self.heightmap.size();
+        let (basin, queue) = match &self.fill {
+            Some(fill) => (fill.basin.clone(), fill.search_queue.clone()),
+            None => (HashSet::new(), Vec::new()),
+        };
+        let mut frame = format!(
+            "{} basins left, current basin size {}\n",
+            self.low_points.len(),
+            basin.len()
+        );
+        for y in 0..height {
+            for x in 0..width {
+                let pos = (x, y);
+                if queue.contains(&pos) {
+                    frame.push('+');
+                } else if basin.contains(&pos) {
+                    frame.push('~');
+                } else if self.filled.contains(&pos) {
+                    frame.push('#');
+                } else if let Some(height) = self.heightmap.points.get(&pos) {
+                    frame.push_str(&height.to_string());
+                }
+            }
+            frame.push('\n');
+        }
+        frame
+    }
+
+    fn step(&mut self) -> bool {
+        if let Some(fill) = &mut self.fill {
+            if fill.step(&self.heightmap) {
+                return true;
+            }
+            self.filled.extend(fill.basin.drain());
+            self.fill = None;
+            return true;
+        }
+        match self.low_points.pop() {
+            Some(pos) => {
+                self.fill = Some(FloodFill::new(pos));
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+pub fn part1(input: &str, _cancel: &CancelToken) -> Result<String, SolveError> {
+    Ok(Heightmap::from_str(input)?.risk_level().to_string())
+}
+
+pub fn part2(input: &str, _cancel: &CancelToken) -> Result<String, SolveError> {
+    Ok(Heightmap::from_str(input)?
+        .three_largest_basin_size()
+        .to_string())
+}
+
+/// Draws the heightmap from light valleys to dark ridges, with the low points in red.
+pub fn render(input: &str) -> Result<String, SolveError> {
+    let heightmap = Heightmap::from_str(input)?;
+    let (width, height) = heightmap.size();
+    let mut svg = Svg::new(width as u32, height as u32);
+    for (&(x, y), &h) in heightmap.points.iter().sorted() {
+        let shade = 255 - h * 25;
+        let fill = format!("rgb({},{},{})", shade, shade, 255);
+        svg.rect(x as u32, y as u32, 1, 1, &fill);
+    }
+    for (x, y) in heightmap.low_points().sorted() {
+        svg.rect(x as u32, y as u32, 1, 1, "red");
+    }
+    Ok(svg.to_string())
+}
+
+/// Draws the heightmap as text, the ridges of height 9 that separate the basins as `#` and the
+/// low points as `o`.
+pub fn grid(input: &str) -> Result<String, SolveError> {
+    let heightmap = Heightmap::from_str(input)?;
+    let (width, height) = heightmap.size();
+    let low_points: HashSet<_> = heightmap.low_points().collect();
+    let mut grid = String::new();
+    for y in 0..height {
+        for x in 0..width {
+            grid.push(match heightmap.points.get(&(x, y)) {
+                _ if low_points.contains(&(x, y)) => 'o',
+                Some(9) => '#',
+                _ => '.',
+            });
+        }
+        grid.push('\n');
+    }
+    Ok(grid)
+}
+
+/// Every low point from top to bottom with the size of its basin.
+pub fn trace(input: &str) -> Result<String, SolveError> {
+    let heightmap = Heightmap::from_str(input)?;
+    Ok(heightmap
+        .low_points()
+        .sorted_by_key(|&(x, y)| (y, x))
+        .map(|(x, y)| {
+            format!(
+                "low point ({:>2}, {:>3}) height {}: basin size {}\n",
+                x,
+                y,
+                heightmap.points[&(x, y)],
+                heightmap.basin(&(x, y)).len()
+            )
+        })
+        .collect::<String>())
+}
+
 pub fn run() {
     let heightmap = Heightmap::from_str(include_str!("data/9")).unwrap();
     println!("{}", heightmap.risk_level());
@@ -82,6 +280,7 @@ pub fn run() {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::utils::snapshot::assert_snapshot;
 
     #[test]
     fn test_find_low_points() {
@@ -108,4 +307,60 @@ mod tests {
         .unwrap();
         assert_eq!(heightmap.three_largest_basin_size(), 1134);
     }
+
+    #[test]
+    fn test_basins_snapshot() {
+        assert_snapshot("day9_basins", &trace(include_str!("data/9")).unwrap());
+    }
+
+    #[test]
+    fn test_lint() {
+        let errors = lint("219\n3a8\n98");
+        assert_eq!(
+            errors
+                .iter()
+                .map(|err| (err.line, err.column))
+                .collect::<Vec<_>>(),
+            vec![(2, 2), (3, 1)]
+        );
+    }
+
+    #[test]
+    fn test_render() {
+        let svg = render("21\n39").unwrap();
+        assert!(svg.contains(r#"viewBox="0 0 2 2""#));
+        assert!(svg.contains(r#"<rect x="1" y="1" width="1" height="1" fill="rgb(30,30,255)"/>"#));
+        assert!(svg.ends_with(r#"<rect x="1" y="0" width="1" height="1" fill="red"/></svg>"#));
+    }
+
+    #[test]
+    fn test_animation() {
+        let mut animation = BasinAnimation::from_str(
+            "2199943210
+3987894921
+9856789892
+8767896789
+9899965678",
+        )
+        .unwrap();
+        assert!(animation
+            .frame()
+            .starts_with("4 basins left, current basin size 0\n"));
+        while animation.step() {}
+        assert_eq!(
+            animation.frame(),
+            "0 basins left, current basin size 0
+##999#####
+#9###9#9##
+9#####9#9#
+#####9###9
+9#999#####
+"
+        );
+    }
+
+    #[test]
+    fn test_grid() {
+        assert_eq!(grid("2199\n3987").unwrap(), ".o##\n.#.o\n");
+    }
 }
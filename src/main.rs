@@ -1,4 +1,9 @@
 #![allow(unused)]
+mod allocations;
+mod animation;
+mod cache;
+mod config;
+mod dashboard;
 mod day1;
 mod day10;
 mod day2;
@@ -9,8 +14,693 @@ mod day6;
 mod day7;
 mod day8;
 mod day9;
+mod plugin;
+mod report;
+mod runner;
+mod server;
+mod solvers;
 mod utils;
 
+use animation::{Animator, Simulation};
+use config::{Config, Format};
+use day1::analysis::{self, Detector};
+use day1::filters::{self, Filter};
+use day1::SonarCounter;
+use day2::models::{self, NavigationModel};
+use day2::planner;
+use day2::safety::{self, Limits};
+use day2::trajectory::{self, Trajectory};
+use plugin::Plugin;
+use std::fs::File;
+use std::io::{BufReader, IsTerminal};
+use std::path::{Path, PathBuf};
+use std::process::exit;
+use std::str::FromStr;
+use std::time::Duration;
+use utils::cancel::CancelToken;
+use utils::json::Json;
+use utils::parse::integer;
+use utils::stream::LineSource;
+
+#[global_allocator]
+static ALLOCATOR: allocations::CountingAllocator = allocations::CountingAllocator;
+
+fn usage() -> ! {
+    eprintln!("usage: aoc2021 animate <day> [--fps <n>] [--input <file>] [--headless <file>]");
+    eprintln!("       aoc2021 bench <day|all> [--inputs <dir>] [--examples] [--iterations <n>] [--timeout <seconds>] [--plugin <executable>]");
+    eprintln!("       aoc2021 dashboard [--timeout <seconds>] [--no-cache]");
+    eprintln!("       aoc2021 lint <day> <file>");
+    eprintln!("       aoc2021 navigate [--model <name>] [--compare <name>] [--input <script>] [--trajectory <csv|svg|plan> | [--min-depth <n>] [--max-depth <n>] [--max-aim <n>] [--max-distance <n>] [--strict]]");
+    eprintln!("       aoc2021 navigate --plan <distance>,<depth> [--model <name>] [--max-step <n>] [--max-aim <n>] [--negative-depth]");
+    eprintln!("       aoc2021 run <day|all> [--inputs <dir>] [--examples] [--format <table|json>] [--timeout <seconds>] [--no-cache] [--clear-cache] [--plugin <executable>]");
+    eprintln!(
+        "       aoc2021 run <day> --stream <file> [--format <table|json>] [--timeout <seconds>]"
+    );
+    eprintln!("       aoc2021 report <day|all> [--inputs <dir>] [--examples] [--output <file>] [--timeout <seconds>] [--no-cache] [--plugin <executable>]");
+    eprintln!("       aoc2021 serve [--port <n>] [--timeout <seconds>]");
+    eprintln!("       aoc2021 sonar <window> [--input <file>] [--sums]");
+    eprintln!(
+        "       aoc2021 sonar <window> --analyze [--input <file>] [--jump <n> | --z-score <x>]"
+    );
+    eprintln!("       aoc2021 sonar <window>[,<window>...] --live");
+    eprintln!("       aoc2021 sonar <window> --filter <sma:n|ema:x|median:n|hysteresis:x> [--input <file>]");
+    eprintln!("settings are read from aoc.toml in the project or .aoc.toml in the home directory,");
+    eprintln!("or the file given with --config <file>; flags override them");
+    eprintln!("a plugin is called with the day and part as arguments and the input on stdin,");
+    eprintln!("it prints its answers as JSON on stdout, like {{\"part1\": \"42\"}}");
+    exit(2)
+}
+
+fn fail(message: String) -> ! {
+    eprintln!("{}", message);
+    exit(1)
+}
+
+fn flag(args: &[String], name: &str) -> Option<String> {
+    args.iter()
+        .position(|arg| arg == name)
+        .map(|index| args.get(index + 1).cloned().unwrap_or_else(|| usage()))
+}
+
+fn read_input(args: &[String], default: &'static str) -> String {
+    match flag(args, "--input") {
+        Some(path) => std::fs::read_to_string(&path)
+            .unwrap_or_else(|err| fail(format!("could not read {}: {}", path, err))),
+        None => default.to_owned(),
+    }
+}
+
+fn animate(args: &[String]) {
+    let day = args.first().and_then(|day| day.parse::<u8>().ok());
+    let simulation: Result<Box<dyn Simulation>, String> = match day {
+        Some(4) => day4::BingoAnimation::from_str(&read_input(args, include_str!("data/4")))
+            .map(|animation| Box::new(animation) as Box<dyn Simulation>)
+            .map_err(|err| err.to_string()),
+        Some(6) => day6::PopulationAnimation::new(&read_input(args, include_str!("data/6")), 256)
+            .map(|animation| Box::new(animation) as Box<dyn Simulation>)
+            .map_err(|err| err.to_string()),
+        Some(9) => day9::BasinAnimation::from_str(&read_input(args, include_str!("data/9")))
+            .map(|animation| Box::new(animation) as Box<dyn Simulation>)
+            .map_err(|err| err.to_string()),
+        _ => fail("only days 4, 6 and 9 can be animated".to_owned()),
+    };
+    let mut simulation =
+        simulation.unwrap_or_else(|err| fail(format!("could not parse input: {}", err)));
+    let result = match flag(args, "--headless") {
+        Some(path) => File::create(&path)
+            .and_then(|file| animation::record(simulation.as_mut(), file))
+            .map(|frames| println!("wrote {} frames to {}", frames, path)),
+        None => {
+            let fps = flag(args, "--fps")
+                .map(|fps| fps.parse().unwrap_or_else(|_| usage()))
+                .unwrap_or(10.0);
+            Animator::new(fps).play(simulation.as_mut())
+        }
+    };
+    if let Err(err) = result {
+        fail(format!("animation failed: {}", err));
+    }
+}
+
+/// Day 1 with any window size: how often the window sums increase, the sums themselves, or an
+/// analysis of the sweep as CSV. With a filter the depths are smoothed first, reading the input a
+/// line at a time. Live, depths are read from stdin as they arrive and the counts so far are
+/// printed after each one.
+fn sonar(args: &[String]) {
+    if args.iter().any(|arg| arg == "--live") {
+        let sizes: Vec<usize> = match args.first() {
+            Some(sizes) => sizes
+                .split(',')
+                .map(|size| match size.parse() {
+                    Ok(size @ 1..) => size,
+                    _ => usage(),
+                })
+                .collect(),
+            None => usage(),
+        };
+        let mut counter = SonarCounter::new(&sizes);
+        let cancel = CancelToken::new();
+        let stdin = std::io::stdin();
+        for depth in LineSource::new(stdin.lock(), &cancel).parse(integer::<i32>) {
+            let depth = depth.unwrap_or_else(|err| fail(err.to_string()));
+            counter.push(depth);
+            let counts = counter
+                .counts()
+                .iter()
+                .map(|(size, count)| format!("{}:{}", size, count))
+                .collect::<Vec<_>>()
+                .join(" ");
+            println!("{} {}", depth, counts);
+        }
+        return;
+    }
+    let size = match args.first().and_then(|size| size.parse().ok()) {
+        Some(size @ 1..) => size,
+        _ => usage(),
+    };
+    if let Some(filter) = flag(args, "--filter") {
+        let filter: Filter = filter.parse().unwrap_or_else(|err| fail(err));
+        let cancel = CancelToken::new();
+        let increases = match flag(args, "--input") {
+            Some(path) => {
+                let file = File::open(&path)
+                    .unwrap_or_else(|err| fail(format!("could not read {}: {}", path, err)));
+                filters::stream_smoothed_increases(&mut BufReader::new(file), &cancel, filter, size)
+            }
+            None => filters::stream_smoothed_increases(
+                &mut include_str!("data/1").as_bytes(),
+                &cancel,
+                filter,
+                size,
+            ),
+        };
+        match increases {
+            Ok(increases) => println!("{}", increases),
+            Err(err) => fail(err.to_string()),
+        }
+        return;
+    }
+    let depths = day1::parse(&read_input(args, include_str!("data/1")))
+        .unwrap_or_else(|err| fail(format!("could not parse input: {}", err)));
+    if args.iter().any(|arg| arg == "--analyze") {
+        let detector = match (flag(args, "--jump"), flag(args, "--z-score")) {
+            (Some(jump), None) => Detector::Jump(jump.parse().unwrap_or_else(|_| usage())),
+            (None, Some(limit)) => Detector::ZScore(limit.parse().unwrap_or_else(|_| usage())),
+            (None, None) => analysis::Options::default().detector,
+            (Some(_), Some(_)) => usage(),
+        };
+        let options = analysis::Options {
+            window: size,
+            detector,
+        };
+        print!("{}", analysis::Analysis::new(&depths, &options).to_csv());
+    } else if args.iter().any(|arg| arg == "--sums") {
+        for sum in day1::window_sums(depths, size) {
+            println!("{}", sum);
+        }
+    } else {
+        println!("{}", day1::count_window_increases(depths, size));
+    }
+}
+
+fn navigation_model(name: &str) -> Box<dyn NavigationModel> {
+    models::model(name).unwrap_or_else(|| {
+        fail(format!(
+            "unknown model {:?}, expected one of {}, optionally prefixed with surfaced-",
+            name,
+            models::NAMES.join(", ")
+        ))
+    })
+}
+
+/// Day 2 with any navigation model, printing where the submarine ends up, or its whole path as
+/// CSV, an SVG depth profile or an SVG plan view, optionally next to the path of a second model.
+/// The input may be a script, see `day2::script`.
+fn navigate(args: &[String]) {
+    let name = flag(args, "--model").unwrap_or_else(|| "aimed".to_owned());
+    if let Some(target) = flag(args, "--plan") {
+        plan_course(args, &target, navigation_model(&name).as_ref());
+        return;
+    }
+    let mut names = vec![name];
+    names.extend(flag(args, "--compare"));
+    let commands = day2::script::compile(&read_input(args, include_str!("data/2")))
+        .unwrap_or_else(|err| fail(format!("could not parse script: {}", err)));
+    for name in &names {
+        if navigation_model(name).steers() {
+            continue;
+        }
+        let turn = commands.iter().position(|command| {
+            matches!(command, day2::Command::Left(_) | day2::Command::Right(_))
+        });
+        if let Some(index) = turn {
+            fail(format!(
+                "model {} cannot steer, command {} ({}) turns, try the 3d model",
+                name,
+                index + 1,
+                commands[index]
+            ));
+        }
+    }
+    let format = flag(args, "--trajectory");
+    if format.is_none() {
+        check_course(args, &names, &commands);
+        return;
+    }
+    let trajectories: Vec<_> = names
+        .iter()
+        .map(|name| {
+            Trajectory::record(commands.iter().copied(), navigation_model(name).as_ref())
+                .unwrap_or_else(|err| fail(format!("model {}: {}", name, err)))
+        })
+        .collect();
+    let named: Vec<_> = names
+        .iter()
+        .map(String::as_str)
+        .zip(&trajectories)
+        .collect();
+    match format.as_deref() {
+        Some("csv") => print!("{}", trajectory::to_csv(&named)),
+        Some("svg") => println!("{}", trajectory::depth_profile(&named)),
+        Some("plan") => println!("{}", trajectory::plan_view(&named)),
+        _ => usage(),
+    }
+}
+
+/// Prints where each model takes the submarine, after every command that breaks a safety limit
+/// given on the command line. In strict mode a model stops at the first one, which fails the run.
+fn check_course(args: &[String], names: &[String], commands: &[day2::Command]) {
+    let limit = |name| flag(args, name).map(|limit| limit.parse().unwrap_or_else(|_| usage()));
+    let limits = Limits {
+        min_depth: limit("--min-depth"),
+        max_depth: limit("--max-depth"),
+        max_aim: magnitude(args, "--max-aim"),
+        max_distance: magnitude(args, "--max-distance"),
+    };
+    let strict = args.iter().any(|arg| arg == "--strict");
+    let mut stopped = false;
+    for name in names {
+        let model = navigation_model(name);
+        let checked = safety::check(commands.iter().copied(), model.as_ref(), &limits, strict)
+            .unwrap_or_else(|err| fail(format!("model {}: {}", name, err)));
+        for violation in &checked.violations {
+            println!("{}: {}", name, violation);
+        }
+        let submarine = checked.submarine;
+        // only models that steer leave the x axis
+        let plane = if (submarine.x, submarine.y, submarine.heading) == (0.0, 0.0, 0) {
+            String::new()
+        } else {
+            format!(
+                " x {:.3} y {:.3} heading {}",
+                submarine.x, submarine.y, submarine.heading
+            )
+        };
+        println!(
+            "{}: {} at distance {} depth {} aim {} position {}{}",
+            name,
+            if checked.stopped { "stopped" } else { "ended" },
+            submarine.distance,
+            submarine.depth,
+            submarine.aim,
+            submarine.position(),
+            plane
+        );
+        stopped |= checked.stopped;
+    }
+    if stopped {
+        fail("stopped at a safety limit".to_owned());
+    }
+}
+
+/// A limit that holds either way, so it cannot be negative.
+fn magnitude(args: &[String], name: &str) -> Option<i32> {
+    let limit: i32 = flag(args, name)?.parse().unwrap_or_else(|_| usage());
+    if limit < 0 {
+        fail(format!("{} cannot be negative, it limits both ways", name));
+    }
+    Some(limit)
+}
+
+/// Prints a shortest script that takes the submarine to the target, given as `distance,depth`.
+fn plan_course(args: &[String], target: &str, model: &dyn NavigationModel) {
+    let target = match target.split_once(',').map(|(x, y)| (x.parse(), y.parse())) {
+        Some((Ok(distance), Ok(depth))) => (distance, depth),
+        _ => usage(),
+    };
+    let defaults = planner::Constraints::default();
+    let constraints = planner::Constraints {
+        max_step: flag(args, "--max-step").map_or(defaults.max_step, |step| {
+            step.parse().unwrap_or_else(|_| usage())
+        }),
+        negative_depth: args.iter().any(|arg| arg == "--negative-depth"),
+        max_aim: magnitude(args, "--max-aim"),
+    };
+    let course = planner::plan(model, target, &constraints).unwrap_or_else(|| {
+        fail(format!(
+            "found no short enough course to distance {} depth {} within the constraints",
+            target.0, target.1
+        ))
+    });
+    for command in course {
+        match command {
+            day2::Command::Forward(num) if num < 0 => println!("back {}", -num),
+            command => println!("{}", command),
+        }
+    }
+}
+
+fn lint(args: &[String]) {
+    let (day, path) = match args {
+        [day, path] => (day.parse::<u8>().unwrap_or_else(|_| usage()), path),
+        _ => usage(),
+    };
+    let input = std::fs::read_to_string(path)
+        .unwrap_or_else(|err| fail(format!("could not read {}: {}", path, err)));
+    let errors = match day {
+        1 => day1::lint(&input),
+        2 => day2::lint(&input),
+        3 => day3::lint(&input),
+        4 => day4::lint(&input),
+        5 => day5::lint(&input),
+        6 => day6::lint(&input),
+        7 => day7::lint(&input),
+        8 => day8::lint(&input),
+        9 => day9::lint(&input),
+        10 => day10::lint(&input),
+        _ => fail(format!("there is no day {}", day)),
+    };
+    for error in &errors {
+        println!("{}: {}", path, error);
+    }
+    if !errors.is_empty() {
+        fail(format!("{} problems found", errors.len()));
+    }
+}
+
+fn timeout(args: &[String]) -> Option<Duration> {
+    flag(args, "--timeout").map(|seconds| match seconds.parse::<f64>() {
+        Ok(seconds) if seconds.is_finite() && seconds >= 0.0 => Duration::from_secs_f64(seconds),
+        _ => usage(),
+    })
+}
+
+fn config(args: &[String]) -> Config {
+    match flag(args, "--config").map(Into::into).or_else(Config::find) {
+        Some(path) => Config::read(&path).unwrap_or_else(|err| fail(err.to_string())),
+        None => Config::default(),
+    }
+}
+
+/// The inputs named on the command line: a directory of inputs for one day, the examples, the
+/// configured inputs or the bundled ones, in that order. Days with a plugin are solved by it.
+fn inputs(args: &[String], config: &Config) -> Vec<(&'static solvers::Solver, runner::Input)> {
+    let solvers: Vec<_> = match args.first().map(String::as_str) {
+        Some("all") => solvers::SOLVERS.iter().collect(),
+        Some(day) => match day.parse().ok().and_then(solvers::solver) {
+            Some(solver) => vec![solver],
+            None => fail(format!("there is no day {}", day)),
+        },
+        None => usage(),
+    };
+    let plugin = flag(args, "--plugin");
+    if plugin.is_some() && solvers.len() != 1 {
+        fail("--plugin needs a single day".to_owned());
+    }
+    let solvers: Vec<_> = solvers
+        .into_iter()
+        .map(|solver| plugged(solver, plugin.as_ref().map(PathBuf::from), config))
+        .collect();
+    match flag(args, "--inputs") {
+        Some(dir) => match solvers.as_slice() {
+            [solver] => runner::read_inputs(Path::new(&dir))
+                .unwrap_or_else(|err| fail(format!("could not read inputs from {}: {}", dir, err)))
+                .into_iter()
+                .map(|input| (*solver, input))
+                .collect(),
+            _ => fail("--inputs needs a single day".to_owned()),
+        },
+        None if args.iter().any(|arg| arg == "--examples") => {
+            let dir = config
+                .examples
+                .as_ref()
+                .unwrap_or_else(|| fail("no examples directory is configured".to_owned()));
+            solvers
+                .into_iter()
+                .flat_map(|solver| {
+                    day_inputs(dir, solver)
+                        .unwrap_or_default()
+                        .into_iter()
+                        .map(move |input| (solver, input))
+                })
+                .collect()
+        }
+        None => solvers
+            .into_iter()
+            .flat_map(|solver| {
+                let inputs = match &config.inputs {
+                    Some(dir) => day_inputs(dir, solver),
+                    None => None,
+                };
+                inputs
+                    .unwrap_or_else(|| vec![runner::Input::bundled(solver)])
+                    .into_iter()
+                    .map(move |input| (solver, input))
+            })
+            .collect(),
+    }
+}
+
+/// The solver for a day, or its plugin if one is given or configured.
+fn plugged(
+    solver: &'static solvers::Solver,
+    plugin: Option<PathBuf>,
+    config: &Config,
+) -> &'static solvers::Solver {
+    match plugin.or_else(|| config.plugins.get(&solver.day).cloned()) {
+        // leaked, it is needed until the process exits like the built in solvers
+        Some(command) => Box::leak(Box::new(solver.with_plugin(Plugin::new(command)))),
+        None => solver,
+    }
+}
+
+fn day_inputs(dir: &Path, solver: &solvers::Solver) -> Option<Vec<runner::Input>> {
+    runner::day_inputs(dir, solver.day).unwrap_or_else(|err| {
+        fail(format!(
+            "could not read inputs for day {} from {}: {}",
+            solver.day,
+            dir.display(),
+            err
+        ))
+    })
+}
+
+fn options(args: &[String], config: &Config) -> runner::Options {
+    let cache = cache::Cache::new(cache::DEFAULT_DIR);
+    if args.iter().any(|arg| arg == "--clear-cache") {
+        cache
+            .clear()
+            .unwrap_or_else(|err| fail(format!("could not clear the cache: {}", err)));
+    }
+    runner::Options {
+        cache: if args.iter().any(|arg| arg == "--no-cache") {
+            None
+        } else {
+            Some(cache)
+        },
+        timeout: timeout(args).or(config.timeout),
+        progress: None,
+    }
+}
+
+fn solve(
+    inputs: &[(&solvers::Solver, runner::Input)],
+    options: &runner::Options,
+) -> Vec<runner::Outcome> {
+    inputs
+        .iter()
+        .flat_map(|(solver, input)| {
+            runner::solve(solver, &input.name, &input.text, &input.expected, options)
+        })
+        .collect()
+}
+
+/// Solves a single day reading the input file a line at a time, for inputs too large to load.
+fn stream(args: &[String], path: &str, options: &runner::Options) -> Vec<runner::Outcome> {
+    let solver = match args.first().and_then(|day| day.parse().ok()) {
+        Some(day) => {
+            solvers::solver(day).unwrap_or_else(|| fail(format!("there is no day {}", day)))
+        }
+        None => fail("--stream needs a single day".to_owned()),
+    };
+    let path = Path::new(path);
+    let expected = runner::read_expected(path).unwrap_or_else(|err| {
+        fail(format!(
+            "could not read answers for {}: {}",
+            path.display(),
+            err
+        ))
+    });
+    let name = path.display().to_string();
+    (1..=2)
+        .zip(expected)
+        .filter_map(|(part, expected)| {
+            let file = File::open(path)
+                .unwrap_or_else(|err| fail(format!("could not open {}: {}", path.display(), err)));
+            let mut reader = BufReader::new(file);
+            let outcome = runner::solve_stream(solver, &name, part, &mut reader, expected, options);
+            if outcome.is_none() {
+                eprintln!("day {} part {} cannot be streamed", solver.day, part);
+            }
+            outcome
+        })
+        .collect()
+}
+
+/// Redraws a progress bar on stderr, the next line of output overwrites it.
+fn progress_bar(progress: &runner::Progress) {
+    const WIDTH: u64 = 30;
+    let total = progress.total.max(1);
+    let filled = (progress.step * WIDTH / total) as usize;
+    eprint!(
+        "\r{} day {} part {} [{:<width$}] {:>3}%\x1b[K",
+        progress.input,
+        progress.day,
+        progress.part,
+        "#".repeat(filled),
+        progress.step * 100 / total,
+        width = WIDTH as usize
+    );
+}
+
+fn progress_json(progress: &runner::Progress) {
+    let event = Json::object([
+        ("event", Json::from("progress")),
+        ("input", Json::from(progress.input)),
+        ("day", Json::from(progress.day)),
+        ("part", Json::from(progress.part)),
+        ("step", Json::from(progress.step)),
+        ("total", Json::from(progress.total)),
+    ]);
+    println!("{}", event);
+}
+
+fn run(args: &[String], config: &Config) {
+    let format = match flag(args, "--format") {
+        Some(format) => format.parse().unwrap_or_else(|_| usage()),
+        None => config.format,
+    };
+    let json = format == Format::Json;
+    let terminal = std::io::stderr().is_terminal();
+    let options = runner::Options {
+        progress: match (json, terminal) {
+            (true, _) => Some(progress_json),
+            (false, true) => Some(progress_bar),
+            (false, false) => None,
+        },
+        ..options(args, config)
+    };
+    let outcomes = match flag(args, "--stream") {
+        Some(path) => stream(args, &path, &options),
+        None => solve(&inputs(args, config), &options),
+    };
+    if json {
+        for outcome in &outcomes {
+            let event = Json::object([
+                ("event", Json::from("outcome")),
+                ("outcome", outcome.to_json()),
+            ]);
+            println!("{}", event);
+        }
+    } else {
+        if terminal {
+            eprint!("\r\x1b[K");
+        }
+        runner::print_table(&outcomes);
+    }
+    let flagged = outcomes
+        .iter()
+        .filter(|outcome| outcome.is_flagged())
+        .count();
+    if flagged > 0 {
+        fail(format!("{} answers flagged", flagged));
+    }
+}
+
+fn report(args: &[String], config: &Config) {
+    let inputs = inputs(args, config);
+    let outcomes = solve(&inputs, &options(args, config));
+    let figures: Vec<_> = inputs
+        .iter()
+        .filter_map(|(solver, input)| {
+            solver.render.map(|render| report::Figure {
+                input: input.name.clone(),
+                day: solver.day,
+                svg: render(&input.text),
+            })
+        })
+        .collect();
+    let path = flag(args, "--output").unwrap_or_else(|| "report.html".to_owned());
+    std::fs::write(&path, report::html(config.year, &outcomes, &figures))
+        .unwrap_or_else(|err| fail(format!("could not write {}: {}", path, err)));
+    println!("wrote {} answers to {}", outcomes.len(), path);
+}
+
+fn bench(args: &[String], config: &Config) {
+    let iterations = flag(args, "--iterations")
+        .map(|iterations| iterations.parse().unwrap_or_else(|_| usage()))
+        .unwrap_or(config.bench_iterations);
+    let options = runner::Options {
+        timeout: timeout(args).or(config.timeout),
+        ..runner::Options::default()
+    };
+    let benchmarks: Vec<_> = inputs(args, config)
+        .iter()
+        .flat_map(|(solver, input)| {
+            let options = &options;
+            (1..=2).map(move |part| runner::bench(solver, input, part, iterations, options))
+        })
+        .collect();
+    runner::print_benchmarks(&benchmarks);
+}
+
+fn dashboard(args: &[String], config: &Config) {
+    if !std::io::stdout().is_terminal() {
+        fail("the dashboard needs a terminal".to_owned());
+    }
+    let days = solvers::SOLVERS
+        .iter()
+        .map(|solver| {
+            let solver = plugged(solver, None, config);
+            let mut inputs = vec![runner::Input::bundled(solver)];
+            if let Some(dir) = &config.inputs {
+                inputs.extend(day_inputs(dir, solver).unwrap_or_default());
+            }
+            if let Some(dir) = &config.examples {
+                inputs.extend(day_inputs(dir, solver).unwrap_or_default().into_iter().map(
+                    |input| runner::Input {
+                        name: format!("example {}", input.name),
+                        ..input
+                    },
+                ));
+            }
+            dashboard::Day { solver, inputs }
+        })
+        .collect();
+    if let Err(err) = dashboard::Dashboard::new(days, options(args, config)).run() {
+        fail(format!("dashboard failed: {}", err));
+    }
+}
+
+fn serve(args: &[String], config: &Config) {
+    let port: u16 = flag(args, "--port")
+        .map(|port| port.parse().unwrap_or_else(|_| usage()))
+        .unwrap_or(2021);
+    let server = server::Server::bind(("127.0.0.1", port), timeout(args).or(config.timeout))
+        .unwrap_or_else(|err| fail(format!("could not listen on port {}: {}", port, err)));
+    if let Ok(addr) = server.local_addr() {
+        println!("listening on http://{}", addr);
+    }
+    if let Err(err) = server.serve() {
+        fail(format!("server failed: {}", err));
+    }
+}
+
 fn main() {
-    day10::run();
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    // only the commands that use the settings read them, so a broken file leaves the rest working
+    let rest = args.get(1..).unwrap_or_default();
+    match args.first().map(String::as_str) {
+        None => day10::run(),
+        Some("animate") => animate(rest),
+        Some("bench") => bench(rest, &config(rest)),
+        Some("dashboard") => dashboard(rest, &config(rest)),
+        Some("lint") => lint(rest),
+        Some("navigate") => navigate(rest),
+        Some("report") => report(rest, &config(rest)),
+        Some("run") => run(rest, &config(rest)),
+        Some("serve") => serve(rest, &config(rest)),
+        Some("sonar") => sonar(rest),
+        Some(_) => usage(),
+    }
 }
@@ -0,0 +1,238 @@
+//! External solvers, so solutions prototyped in other languages are timed and verified by the
+//! same runner. A plugin is an executable called with the day and part as arguments:
+//!
+//! ```sh
+//! plugins/day1.sh 1 2 < input
+//! ```
+//!
+//! It reads the puzzle input on stdin and prints a JSON object with its answers on stdout, such
+//! as `{"part2": "1761"}`. Answers may be strings or numbers, give large ones as strings since
+//! JSON numbers lose precision past 2^53. A plugin may answer both parts at once, only the part
+//! asked for is used. It fails by exiting with a non-zero status, stderr says why. Plugins are
+//! only run on unix, where anything they start can be stopped with them.
+
+use crate::solvers::SolveError;
+use crate::utils::cancel::CancelToken;
+use crate::utils::json::Json;
+use std::io::{Read, Write};
+#[cfg(unix)]
+use std::os::unix::process::CommandExt;
+use std::path::PathBuf;
+use std::process::{Child, Command, Stdio};
+use std::thread;
+use std::time::Duration;
+
+/// How often a running plugin is checked for having finished or run out of time.
+const POLL_INTERVAL: Duration = Duration::from_millis(1);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Plugin {
+    pub command: PathBuf,
+}
+
+impl Plugin {
+    pub fn new(command: impl Into<PathBuf>) -> Self {
+        Self {
+            command: command.into(),
+        }
+    }
+
+    /// Runs the plugin for one part, killing it once the token is cancelled.
+    #[cfg(unix)]
+    pub fn solve(
+        &self,
+        day: u8,
+        part: u8,
+        input: &str,
+        cancel: &CancelToken,
+    ) -> Result<String, SolveError> {
+        let fail = |message: String| SolveError::Plugin(message);
+        let mut child = Command::new(&self.command)
+            .arg(day.to_string())
+            .arg(part.to_string())
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            // its own process group, so anything it starts can be stopped along with it
+            .process_group(0)
+            .spawn()
+            .map_err(|err| fail(format!("could not run {}: {}", self.command.display(), err)))?;
+        let (mut stdin, mut stdout, mut stderr) = (
+            child.stdin.take().unwrap(),
+            child.stdout.take().unwrap(),
+            child.stderr.take().unwrap(),
+        );
+        // the pipes are served from threads so a plugin writing a lot before it has read all of
+        // its input cannot deadlock with us. They finish once the last process holding the pipes
+        // is gone, which is not waited for if the plugin is stopped.
+        let input = input.to_owned();
+        thread::spawn(move || {
+            // a plugin that exits without reading its input is not an error in itself
+            let _ = stdin.write_all(input.as_bytes());
+        });
+        let stdout = thread::spawn(move || {
+            let mut output = String::new();
+            stdout.read_to_string(&mut output).map(|_| output)
+        });
+        let stderr = thread::spawn(move || {
+            let mut output = String::new();
+            let _ = stderr.read_to_string(&mut output);
+            output
+        });
+        let status = loop {
+            match child.try_wait() {
+                Ok(Some(status)) => break status,
+                Ok(None) if cancel.is_cancelled() => {
+                    stop(&mut child);
+                    return Err(SolveError::Timeout);
+                }
+                Ok(None) => thread::sleep(POLL_INTERVAL),
+                Err(err) => {
+                    stop(&mut child);
+                    return Err(fail(format!("could not wait for the plugin: {}", err)));
+                }
+            }
+        };
+        // whatever the plugin left running could hold on to the pipes for as long as it likes
+        kill_group(&child);
+        let (stdout, stderr) = (stdout.join().unwrap(), stderr.join().unwrap());
+        if !status.success() {
+            return Err(fail(match stderr.trim() {
+                "" => format!("exited with {}", status),
+                message => format!("exited with {}: {}", status, message),
+            }));
+        }
+        let stdout = stdout.map_err(|err| fail(format!("could not read its output: {}", err)))?;
+        let output: Json = stdout
+            .parse()
+            .map_err(|err| fail(format!("invalid output, {}", err)))?;
+        match output.get(&format!("part{}", part)) {
+            Some(Json::String(answer)) => Ok(answer.clone()),
+            Some(answer @ Json::Number(_)) => Ok(answer.to_string()),
+            Some(answer) => Err(fail(format!(
+                "expected a string or number, found {}",
+                answer
+            ))),
+            None => Err(fail(format!("no answer for part {}", part))),
+        }
+    }
+
+    #[cfg(not(unix))]
+    pub fn solve(
+        &self,
+        _day: u8,
+        _part: u8,
+        _input: &str,
+        _cancel: &CancelToken,
+    ) -> Result<String, SolveError> {
+        Err(SolveError::Plugin(
+            "plugins can only be run on unix".to_owned(),
+        ))
+    }
+}
+
+#[cfg(unix)]
+extern "C" {
+    /// Sends a signal, to a whole process group if the pid is negative.
+    fn kill(pid: i32, signal: i32) -> i32;
+}
+
+#[cfg(unix)]
+const SIGKILL: i32 = 9;
+
+/// Kills everything in the plugin's process group, which it leads. The group outlives the plugin
+/// for as long as anything it started is still running.
+#[cfg(unix)]
+fn kill_group(child: &Child) {
+    // a group that is already gone fails with ESRCH, which leaves nothing to do
+    unsafe {
+        kill(-(child.id() as i32), SIGKILL);
+    }
+}
+
+/// Kills the plugin and everything it started.
+#[cfg(unix)]
+fn stop(child: &mut Child) {
+    kill_group(child);
+    let _ = child.kill();
+    let _ = child.wait();
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::os::unix::fs::PermissionsExt;
+    use std::path::Path;
+    use std::time::Instant;
+
+    fn script(name: &str, body: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("aoc2021-plugin-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(name);
+        fs::write(&path, format!("#!/bin/sh\n{}\n", body)).unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_solve() {
+        // counts the input lines for part 1, reports its arguments for part 2
+        let plugin = Plugin::new(script(
+            "count",
+            r#"lines=$(wc -l | tr -d ' ')
+echo "{\"part1\": $lines, \"part2\": \"day $1 part $2\"}""#,
+        ));
+        let cancel = CancelToken::new();
+        assert_eq!(plugin.solve(4, 1, "a\nb\nc\n", &cancel), Ok("3".to_owned()));
+        assert_eq!(
+            plugin.solve(4, 2, "", &cancel),
+            Ok("day 4 part 2".to_owned())
+        );
+    }
+
+    #[test]
+    fn test_solve_errors() {
+        let cancel = CancelToken::new();
+        let plugin = Plugin::new(script("fail", "echo 'no idea' >&2; exit 3"));
+        assert_eq!(
+            plugin.solve(1, 1, "", &cancel),
+            Err(SolveError::Plugin(
+                "exited with exit status: 3: no idea".to_owned()
+            ))
+        );
+        let plugin = Plugin::new(script("garbage", "echo answer: 42"));
+        assert!(matches!(
+            plugin.solve(1, 1, "", &cancel),
+            Err(SolveError::Plugin(message)) if message.starts_with("invalid output")
+        ));
+        let plugin = Plugin::new(script("other", "echo '{\"part1\": 1}'"));
+        assert_eq!(
+            plugin.solve(1, 2, "", &cancel),
+            Err(SolveError::Plugin("no answer for part 2".to_owned()))
+        );
+        let plugin = Plugin::new(Path::new("/nonexistent/plugin"));
+        assert!(plugin.solve(1, 1, "", &cancel).is_err());
+    }
+
+    #[test]
+    fn test_solve_timeout() {
+        let plugin = Plugin::new(script("slow", "exec sleep 10"));
+        let cancel = CancelToken::with_timeout(Duration::from_millis(20));
+        assert_eq!(plugin.solve(1, 1, "", &cancel), Err(SolveError::Timeout));
+        // the shell waits for a sleep it started, which holds on to the pipes too
+        let plugin = Plugin::new(script("slower", "sleep 10; echo '{}'"));
+        let start = Instant::now();
+        let cancel = CancelToken::with_timeout(Duration::from_millis(20));
+        assert_eq!(plugin.solve(1, 1, "", &cancel), Err(SolveError::Timeout));
+        assert!(start.elapsed() < Duration::from_secs(5));
+        // the plugin answers and exits, but leaves a sleep behind that holds on to its stdout
+        let plugin = Plugin::new(script("leftover", "sleep 10 &\necho '{\"part1\": 1}'"));
+        let start = Instant::now();
+        assert_eq!(
+            plugin.solve(1, 1, "", &CancelToken::new()),
+            Ok("1".to_owned())
+        );
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+}
@@ -0,0 +1,179 @@
+use crate::runner::Outcome;
+use crate::solvers::SolveError;
+use itertools::Itertools;
+use std::fmt::Write;
+
+const STYLE: &str =
+    "body { font-family: sans-serif; margin: 2em auto; max-width: 60em; color: #222; }
+table { border-collapse: collapse; width: 100%; }
+th, td { text-align: left; padding: 0.25em 0.75em; border-bottom: 1px solid #ddd; }
+td.number { text-align: right; font-variant-numeric: tabular-nums; }
+.correct { color: #1a7f37; }
+.unverified { color: #666; }
+.wrong, .failed { color: #cf222e; font-weight: bold; }
+figure { margin: 1em 0; }
+figure svg { width: 100%; max-width: 40em; height: auto; border: 1px solid #ddd; }";
+
+/// A drawing of one input, for the days that have one.
+#[derive(Debug, Clone)]
+pub struct Figure {
+    pub input: String,
+    pub day: u8,
+    pub svg: Result<String, SolveError>,
+}
+
+fn escape(s: &str) -> String {
+    s.chars()
+        .map(|c| match c {
+            '&' => "&amp;".to_owned(),
+            '<' => "&lt;".to_owned(),
+            '>' => "&gt;".to_owned(),
+            '"' => "&quot;".to_owned(),
+            '\'' => "&#39;".to_owned(),
+            c => c.to_string(),
+        })
+        .collect()
+}
+
+fn bytes(n: u64) -> String {
+    match n {
+        0..=1023 => format!("{} B", n),
+        1024..=1048575 => format!("{:.1} KiB", n as f64 / 1024.0),
+        _ => format!("{:.1} MiB", n as f64 / 1048576.0),
+    }
+}
+
+fn write_row(html: &mut String, outcome: &Outcome) {
+    let (time, count, size) = if outcome.cached {
+        ("cached".to_owned(), "-".to_owned(), "-".to_owned())
+    } else {
+        (
+            format!("{:.2?}", outcome.elapsed),
+            outcome.allocations.count.to_string(),
+            bytes(outcome.allocations.bytes),
+        )
+    };
+    let _ = writeln!(
+        html,
+        r#"<tr><td>{}</td><td>{}</td><td>{}</td><td class="{}">{}</td><td class="number">{}</td><td class="number">{}</td><td class="number">{}</td></tr>"#,
+        escape(&outcome.input),
+        outcome.part,
+        escape(outcome.answer.as_deref().unwrap_or("-")),
+        outcome.status().name(),
+        escape(&outcome.describe_status()),
+        time,
+        count,
+        size
+    );
+}
+
+fn write_figure(html: &mut String, figure: &Figure) {
+    match &figure.svg {
+        // the svg is generated by us from numbers and fixed colours, it needs no escaping
+        Ok(svg) => {
+            let _ = writeln!(
+                html,
+                "<figure>{}<figcaption>{}</figcaption></figure>",
+                svg,
+                escape(&figure.input)
+            );
+        }
+        Err(err) => {
+            let _ = writeln!(
+                html,
+                r#"<p class="failed">could not draw {}: {}</p>"#,
+                escape(&figure.input),
+                escape(&err.to_string())
+            );
+        }
+    }
+}
+
+/// A self-contained HTML page with a section per day, it loads nothing from elsewhere.
+pub fn html(year: u16, outcomes: &[Outcome], figures: &[Figure]) -> String {
+    let counts = outcomes.iter().counts_by(|outcome| outcome.status().name());
+    let summary = ["correct", "unverified", "wrong", "failed"]
+        .iter()
+        .map(|name| format!("{} {}", counts.get(name).unwrap_or(&0), name))
+        .join(", ");
+    let mut html = String::new();
+    let _ = writeln!(
+        html,
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n<title>Advent of Code {}</title>\n<style>\n{}\n</style>\n</head>\n<body>\n<h1>Advent of Code {}</h1>\n<p>{} answers: {}</p>",
+        year,
+        STYLE,
+        year,
+        outcomes.len(),
+        summary
+    );
+    for day in outcomes.iter().map(|outcome| outcome.day).unique() {
+        let _ = writeln!(
+            html,
+            "<section id=\"day{}\">\n<h2>Day {}</h2>\n<table>\n<tr><th>input</th><th>part</th><th>answer</th><th>status</th><th>time</th><th>allocations</th><th>allocated</th></tr>",
+            day, day
+        );
+        for outcome in outcomes.iter().filter(|outcome| outcome.day == day) {
+            write_row(&mut html, outcome);
+        }
+        html.push_str("</table>\n");
+        for figure in figures.iter().filter(|figure| figure.day == day) {
+            write_figure(&mut html, figure);
+        }
+        html.push_str("</section>\n");
+    }
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::allocations::Allocations;
+    use std::time::Duration;
+
+    fn outcome(day: u8, part: u8, answer: Result<String, SolveError>) -> Outcome {
+        Outcome {
+            input: "<bundled>".to_owned(),
+            day,
+            part,
+            answer,
+            elapsed: Duration::from_millis(3),
+            cached: false,
+            expected: Some("1".to_owned()),
+            allocations: Allocations {
+                count: 2,
+                bytes: 2048,
+            },
+        }
+    }
+
+    #[test]
+    fn test_html() {
+        let outcomes = [
+            outcome(5, 1, Ok("1".to_owned())),
+            outcome(5, 2, Err(SolveError::Timeout)),
+            outcome(7, 1, Ok("2".to_owned())),
+        ];
+        let figures = [Figure {
+            input: "bundled".to_owned(),
+            day: 5,
+            svg: Ok("<svg></svg>".to_owned()),
+        }];
+        let html = html(2021, &outcomes, &figures);
+        assert!(html.contains("<p>3 answers: 1 correct, 0 unverified, 1 wrong, 1 failed</p>"));
+        assert!(html.contains(
+            r#"<tr><td>&lt;bundled&gt;</td><td>1</td><td>1</td><td class="correct">ok</td><td class="number">3.00ms</td><td class="number">2</td><td class="number">2.0 KiB</td></tr>"#
+        ));
+        assert!(html.contains(r#"<td class="failed">FAILED, timed out</td>"#));
+        assert!(html.contains(r#"<td class="wrong">WRONG, expected 1</td>"#));
+        assert!(html.contains("<figure><svg></svg><figcaption>bundled</figcaption></figure>\n</section>\n<section id=\"day7\">"));
+        assert!(!html.contains("src=") && !html.contains("href=") && !html.contains("<link"));
+    }
+
+    #[test]
+    fn test_bytes() {
+        assert_eq!(bytes(1023), "1023 B");
+        assert_eq!(bytes(1536), "1.5 KiB");
+        assert_eq!(bytes(3 * 1048576), "3.0 MiB");
+    }
+}
@@ -0,0 +1,596 @@
+use crate::allocations::{self, Allocations};
+use crate::cache::Cache;
+use crate::solvers::{SolveError, Solver};
+use crate::utils::cancel::CancelToken;
+use crate::utils::json::Json;
+use std::fs;
+use std::io::{self, BufRead};
+use std::panic;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// Sidecar files next to an input hold its expected answers, one line per part.
+const EXPECTED_EXTENSION: &str = "expected";
+
+/// How far along a solver is, as reported through its cancel token.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Progress<'a> {
+    pub input: &'a str,
+    pub day: u8,
+    pub part: u8,
+    pub step: u64,
+    pub total: u64,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Options {
+    /// Answers are looked up here before solving and stored after.
+    pub cache: Option<Cache>,
+    /// The time budget for each part, solvers that run over it fail with a timeout.
+    pub timeout: Option<Duration>,
+    /// Called as solvers make progress, at most once per percent.
+    pub progress: Option<fn(&Progress)>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    Correct,
+    Unverified,
+    Wrong,
+    Failed,
+}
+
+impl Status {
+    pub fn name(self) -> &'static str {
+        match self {
+            Status::Correct => "correct",
+            Status::Unverified => "unverified",
+            Status::Wrong => "wrong",
+            Status::Failed => "failed",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Outcome {
+    pub input: String,
+    pub day: u8,
+    pub part: u8,
+    pub answer: Result<String, SolveError>,
+    pub elapsed: Duration,
+    pub cached: bool,
+    pub expected: Option<String>,
+    /// What solving allocated, nothing for cached answers.
+    pub allocations: Allocations,
+}
+
+impl Outcome {
+    pub fn status(&self) -> Status {
+        match (&self.answer, &self.expected) {
+            (Err(_), _) => Status::Failed,
+            (Ok(answer), Some(expected)) if answer == expected => Status::Correct,
+            (Ok(_), Some(_)) => Status::Wrong,
+            (Ok(_), None) => Status::Unverified,
+        }
+    }
+
+    pub fn is_flagged(&self) -> bool {
+        matches!(self.status(), Status::Wrong | Status::Failed)
+    }
+
+    pub fn to_json(&self) -> Json {
+        let mut fields = vec![
+            ("input", Json::from(self.input.as_str())),
+            ("day", Json::from(self.day)),
+            ("part", Json::from(self.part)),
+            ("status", Json::from(self.status().name())),
+            ("time_ms", Json::from(self.elapsed.as_secs_f64() * 1000.0)),
+            ("cached", Json::from(self.cached)),
+            ("expected", Json::from(self.expected.clone())),
+        ];
+        match &self.answer {
+            Ok(answer) => fields.push(("answer", Json::from(answer.as_str()))),
+            Err(err) => fields.push(("error", Json::from(err.to_string()))),
+        }
+        Json::object(fields)
+    }
+
+    pub fn describe_status(&self) -> String {
+        match (self.status(), &self.answer, &self.expected) {
+            (Status::Correct, _, _) => "ok".to_owned(),
+            (Status::Unverified, _, _) => "unverified".to_owned(),
+            (Status::Wrong, _, Some(expected)) => format!("WRONG, expected {}", expected),
+            (_, Err(err), _) => format!("FAILED, {}", err),
+            _ => unreachable!(),
+        }
+    }
+}
+
+fn cancel_token(solver: &Solver, input_name: &str, part: u8, options: &Options) -> CancelToken {
+    let mut cancel = options
+        .timeout
+        .map_or_else(CancelToken::new, CancelToken::with_timeout);
+    if let Some(report) = options.progress {
+        let (input, day) = (input_name.to_owned(), solver.day);
+        cancel = cancel.with_progress(move |step, total| {
+            report(&Progress {
+                input: &input,
+                day,
+                part,
+                step,
+                total,
+            })
+        });
+    }
+    cancel
+}
+
+/// Runs a solver, catching panics so one bad input does not abort a whole batch.
+fn catch_panic(
+    solve: impl FnOnce() -> Result<String, SolveError>,
+) -> (Result<String, SolveError>, Allocations) {
+    allocations::measure(|| {
+        panic::catch_unwind(panic::AssertUnwindSafe(solve))
+            .unwrap_or_else(|panic| Err(SolveError::Panic(panic_message(&panic))))
+    })
+}
+
+/// Solves one part of a day. Answers found in the cache are returned without solving. Plugins
+/// are never cached, they can change without their version being bumped.
+pub fn solve_part(
+    solver: &Solver,
+    input_name: &str,
+    part: u8,
+    input: &str,
+    expected: Option<String>,
+    options: &Options,
+) -> Outcome {
+    let start = Instant::now();
+    let cancel = cancel_token(solver, input_name, part, options);
+    let cache = options.cache.as_ref().filter(|_| solver.plugin.is_none());
+    let cached = cache.and_then(|cache| cache.get(solver.day, part, solver.version, input));
+    let (answer, allocations) = match &cached {
+        Some(answer) => (Ok(answer.clone()), Allocations::default()),
+        None => catch_panic(|| solver.solve(part, input, &cancel)),
+    };
+    if let (Some(cache), None, Ok(answer)) = (cache, &cached, &answer) {
+        // a cache that cannot be written only costs time on the next run
+        let _ = cache.put(solver.day, part, solver.version, input, answer);
+    }
+    Outcome {
+        input: input_name.to_owned(),
+        day: solver.day,
+        part,
+        answer,
+        elapsed: start.elapsed(),
+        cached: cached.is_some(),
+        expected,
+        allocations,
+    }
+}
+
+/// Solves one part of a day reading the input a line at a time, so it never has to fit in
+/// memory. Such inputs are neither linted up front nor cached. None if the part cannot stream.
+pub fn solve_stream(
+    solver: &Solver,
+    input_name: &str,
+    part: u8,
+    reader: &mut dyn BufRead,
+    expected: Option<String>,
+    options: &Options,
+) -> Option<Outcome> {
+    let stream = solver.streams[part as usize - 1]?;
+    let start = Instant::now();
+    let cancel = cancel_token(solver, input_name, part, options);
+    let (answer, allocations) = catch_panic(|| stream(reader, &cancel));
+    Some(Outcome {
+        input: input_name.to_owned(),
+        day: solver.day,
+        part,
+        answer,
+        elapsed: start.elapsed(),
+        cached: false,
+        expected,
+        allocations,
+    })
+}
+
+/// A puzzle input with the answers it is expected to have, if known.
+#[derive(Debug, Clone)]
+pub struct Input {
+    pub name: String,
+    pub text: String,
+    pub expected: [Option<String>; 2],
+}
+
+impl Input {
+    pub fn bundled(solver: &Solver) -> Self {
+        Self {
+            name: "bundled".to_owned(),
+            text: solver.input.to_owned(),
+            expected: [None, None],
+        }
+    }
+}
+
+/// Solves both parts of a day for one input.
+pub fn solve(
+    solver: &Solver,
+    input_name: &str,
+    input: &str,
+    expected: &[Option<String>; 2],
+    options: &Options,
+) -> Vec<Outcome> {
+    (1..=2)
+        .map(|part| {
+            let expected = expected[part as usize - 1].clone();
+            solve_part(solver, input_name, part, input, expected, options)
+        })
+        .collect()
+}
+
+fn panic_message(panic: &Box<dyn std::any::Any + Send>) -> String {
+    if let Some(message) = panic.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = panic.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "solver panicked".to_owned()
+    }
+}
+
+fn expected_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_owned();
+    name.push(".");
+    name.push(EXPECTED_EXTENSION);
+    path.with_file_name(name)
+}
+
+/// Reads the expected answers for an input, if it has a sidecar file.
+pub fn read_expected(path: &Path) -> io::Result<[Option<String>; 2]> {
+    let mut expected = [None, None];
+    match fs::read_to_string(expected_path(path)) {
+        Ok(content) => {
+            for (slot, line) in expected.iter_mut().zip(content.lines()) {
+                let line = line.trim();
+                if !line.is_empty() {
+                    *slot = Some(line.to_owned());
+                }
+            }
+            Ok(expected)
+        }
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(expected),
+        Err(err) => Err(err),
+    }
+}
+
+/// Lists the input files of a directory in name order, skipping expected answer sidecars.
+pub fn input_files(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut paths = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_file()
+            && path.extension().and_then(|ext| ext.to_str()) != Some(EXPECTED_EXTENSION)
+        {
+            paths.push(path);
+        }
+    }
+    paths.sort();
+    Ok(paths)
+}
+
+/// Reads an input file along with its expected answers.
+pub fn read_input(path: &Path) -> io::Result<Input> {
+    Ok(Input {
+        name: path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default(),
+        text: fs::read_to_string(path)?,
+        expected: read_expected(path)?,
+    })
+}
+
+/// Reads every input in a directory along with its expected answers.
+pub fn read_inputs(dir: &Path) -> io::Result<Vec<Input>> {
+    input_files(dir)?
+        .iter()
+        .map(|path| read_input(path))
+        .collect()
+}
+
+/// The inputs of a day in a directory laid out like `src/data`, either a file named by the day
+/// or a directory named by the day holding several inputs. None if there is neither.
+pub fn day_inputs(dir: &Path, day: u8) -> io::Result<Option<Vec<Input>>> {
+    let path = dir.join(day.to_string());
+    if path.is_dir() {
+        read_inputs(&path).map(Some)
+    } else if path.is_file() {
+        Ok(Some(vec![read_input(&path)?]))
+    } else {
+        Ok(None)
+    }
+}
+
+/// Solves every input in a directory.
+pub fn solve_dir(solver: &Solver, dir: &Path, options: &Options) -> io::Result<Vec<Outcome>> {
+    Ok(read_inputs(dir)?
+        .iter()
+        .flat_map(|input| solve(solver, &input.name, &input.text, &input.expected, options))
+        .collect())
+}
+
+/// Timings of solving a part repeatedly, bypassing the cache.
+#[derive(Debug, Clone)]
+pub struct Benchmark {
+    pub input: String,
+    pub day: u8,
+    pub part: u8,
+    /// The first error stops the benchmark, its timings are of the runs before it.
+    pub answer: Result<String, SolveError>,
+    pub times: Vec<Duration>,
+}
+
+pub fn bench(
+    solver: &Solver,
+    input: &Input,
+    part: u8,
+    iterations: u32,
+    options: &Options,
+) -> Benchmark {
+    let options = Options {
+        cache: None,
+        progress: None,
+        ..options.clone()
+    };
+    let mut times = Vec::new();
+    loop {
+        let outcome = solve_part(solver, &input.name, part, &input.text, None, &options);
+        if outcome.answer.is_ok() {
+            times.push(outcome.elapsed);
+        }
+        if outcome.answer.is_err() || times.len() >= iterations as usize {
+            return Benchmark {
+                input: input.name.clone(),
+                day: solver.day,
+                part,
+                answer: outcome.answer,
+                times,
+            };
+        }
+    }
+}
+
+pub fn print_benchmarks(benchmarks: &[Benchmark]) {
+    let rows = benchmarks
+        .iter()
+        .map(|benchmark| {
+            let time = |time: Option<Duration>| {
+                time.map_or("-".to_owned(), |time| format!("{:.2?}", time))
+            };
+            let mean = (!benchmark.times.is_empty())
+                .then(|| benchmark.times.iter().sum::<Duration>() / benchmark.times.len() as u32);
+            [
+                benchmark.input.clone(),
+                benchmark.day.to_string(),
+                benchmark.part.to_string(),
+                benchmark.times.len().to_string(),
+                time(benchmark.times.iter().min().copied()),
+                time(mean),
+                time(benchmark.times.iter().max().copied()),
+                match &benchmark.answer {
+                    Ok(answer) => answer.clone(),
+                    Err(err) => format!("FAILED, {}", err),
+                },
+            ]
+        })
+        .collect::<Vec<_>>();
+    print_columns(
+        [
+            "input", "day", "part", "runs", "min", "mean", "max", "answer",
+        ],
+        &rows,
+    );
+}
+
+pub fn print_table(outcomes: &[Outcome]) {
+    let rows = outcomes
+        .iter()
+        .map(|outcome| {
+            [
+                outcome.input.clone(),
+                outcome.day.to_string(),
+                outcome.part.to_string(),
+                outcome.answer.clone().unwrap_or_else(|_| "-".to_owned()),
+                if outcome.cached {
+                    "cached".to_owned()
+                } else {
+                    format!("{:.2?}", outcome.elapsed)
+                },
+                outcome.describe_status(),
+            ]
+        })
+        .collect::<Vec<_>>();
+    print_columns(["input", "day", "part", "answer", "time", "status"], &rows);
+}
+
+fn print_columns<const N: usize>(header: [&str; N], rows: &[[String; N]]) {
+    let header = header.map(str::to_owned);
+    let mut widths = header.clone().map(|column| column.len());
+    for row in rows {
+        for (width, column) in widths.iter_mut().zip(row) {
+            *width = (*width).max(column.chars().count());
+        }
+    }
+    for row in std::iter::once(&header).chain(rows) {
+        let line = row
+            .iter()
+            .zip(widths)
+            .map(|(column, width)| format!("{:<width$}", column, width = width))
+            .collect::<Vec<_>>()
+            .join("  ");
+        println!("{}", line.trim_end());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solvers;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("aoc2021-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_solve_dir() {
+        let dir = temp_dir("runner");
+        fs::write(dir.join("a.txt"), "1\n2\n1\n3").unwrap();
+        fs::write(dir.join("a.txt.expected"), "2\n5\n").unwrap();
+        fs::write(dir.join("b.txt"), "1\nx").unwrap();
+        fs::write(dir.join("c.txt"), "4\n5").unwrap();
+        let outcomes = solve_dir(solvers::solver(1).unwrap(), &dir, &Options::default()).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(
+            outcomes
+                .iter()
+                .map(|outcome| (outcome.input.as_str(), outcome.part, outcome.status()))
+                .collect::<Vec<_>>(),
+            vec![
+                ("a.txt", 1, Status::Correct),
+                ("a.txt", 2, Status::Wrong),
+                ("b.txt", 1, Status::Failed),
+                ("b.txt", 2, Status::Failed),
+                ("c.txt", 1, Status::Unverified),
+                ("c.txt", 2, Status::Unverified),
+            ]
+        );
+        assert!(outcomes[1].is_flagged());
+        assert!(!outcomes[4].is_flagged());
+    }
+
+    #[test]
+    fn test_day_inputs() {
+        let dir = temp_dir("day-inputs");
+        fs::write(dir.join("1"), "1\n2").unwrap();
+        fs::create_dir(dir.join("2")).unwrap();
+        fs::write(dir.join("2").join("a"), "forward 1").unwrap();
+        fs::write(dir.join("2").join("b"), "down 1").unwrap();
+        let names = |day| {
+            day_inputs(&dir, day).unwrap().map(|inputs| {
+                inputs
+                    .into_iter()
+                    .map(|input| input.name)
+                    .collect::<Vec<_>>()
+            })
+        };
+        assert_eq!(names(1), Some(vec!["1".to_owned()]));
+        assert_eq!(names(2), Some(vec!["a".to_owned(), "b".to_owned()]));
+        assert_eq!(names(3), None);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_solve_cached() {
+        let cache = Cache::new(temp_dir("runner-cache"));
+        let options = Options {
+            cache: Some(cache.clone()),
+            ..Options::default()
+        };
+        let solver = solvers::solver(1).unwrap();
+        let outcomes = solve(solver, "a", "1\n2", &[None, None], &options);
+        assert!(outcomes.iter().all(|outcome| !outcome.cached));
+        let outcomes = solve(solver, "a", "1\n2", &[None, None], &options);
+        assert!(outcomes.iter().all(|outcome| outcome.cached));
+        assert_eq!(outcomes[0].answer, Ok("1".to_owned()));
+        cache.clear().unwrap();
+    }
+
+    #[test]
+    fn test_solve_timeout() {
+        let options = Options {
+            timeout: Some(Duration::from_millis(10)),
+            ..Options::default()
+        };
+        let solver = solvers::solver(7).unwrap();
+        let outcome = solve_part(solver, "far apart", 1, "0,4000000000", None, &options);
+        assert_eq!(outcome.answer, Err(SolveError::Timeout));
+        assert_eq!(outcome.status(), Status::Failed);
+    }
+
+    #[test]
+    fn test_solve_progress() {
+        use std::sync::atomic::{AtomicU64, Ordering};
+        static REPORTS: AtomicU64 = AtomicU64::new(0);
+        static LAST: AtomicU64 = AtomicU64::new(0);
+        let options = Options {
+            progress: Some(|progress| {
+                assert_eq!((progress.input, progress.day, progress.part), ("a", 6, 2));
+                assert_eq!(progress.total, 256);
+                REPORTS.fetch_add(1, Ordering::Relaxed);
+                LAST.fetch_max(progress.step, Ordering::Relaxed);
+            }),
+            ..Options::default()
+        };
+        let outcome = solve_part(
+            solvers::solver(6).unwrap(),
+            "a",
+            2,
+            "3,4,3,1,2",
+            None,
+            &options,
+        );
+        assert_eq!(outcome.answer, Ok("26984457539".to_owned()));
+        // once per percent from 0 up to and including 100
+        assert_eq!(REPORTS.load(Ordering::Relaxed), 101);
+        assert_eq!(LAST.load(Ordering::Relaxed), 256);
+    }
+
+    #[test]
+    fn test_to_json() {
+        let outcome = solve_part(
+            solvers::solver(1).unwrap(),
+            "a",
+            1,
+            "1\n2",
+            Some("1".to_owned()),
+            &Options::default(),
+        );
+        let json = outcome.to_json().to_string();
+        assert!(json.starts_with(r#"{"input":"a","day":1,"part":1,"status":"correct","time_ms":"#));
+        assert!(json.ends_with(r#","cached":false,"expected":"1","answer":"1"}"#));
+    }
+
+    #[test]
+    fn test_bench() {
+        let solver = solvers::solver(1).unwrap();
+        let input = Input {
+            name: "a".to_owned(),
+            text: "1\n2".to_owned(),
+            expected: [None, None],
+        };
+        let benchmark = bench(solver, &input, 1, 3, &Options::default());
+        assert_eq!(benchmark.answer, Ok("1".to_owned()));
+        assert_eq!(benchmark.times.len(), 3);
+        let input = Input {
+            text: "x".to_owned(),
+            ..input
+        };
+        let benchmark = bench(solver, &input, 1, 3, &Options::default());
+        assert!(benchmark.answer.is_err());
+        assert!(benchmark.times.is_empty());
+    }
+
+    #[test]
+    fn test_solve_stream() {
+        let solver = solvers::solver(3).unwrap();
+        let mut reader = "000000000001\n000000000011\n111111111111\n".as_bytes();
+        let outcome = solve_stream(solver, "a", 1, &mut reader, None, &Options::default());
+        assert_eq!(outcome.unwrap().answer, Ok((3 * 4092).to_string()));
+        let mut reader = "".as_bytes();
+        assert!(solve_stream(solver, "a", 2, &mut reader, None, &Options::default()).is_none());
+    }
+}
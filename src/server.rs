@@ -0,0 +1,265 @@
+use crate::runner::{self, Options};
+use crate::solvers::{self, SolveError, SOLVERS};
+use crate::utils::json::Json;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::thread;
+use std::time::Duration;
+
+/// Puzzle inputs are a few kilobytes, anything much larger is a mistake.
+const MAX_BODY: usize = 16 * 1024 * 1024;
+
+#[derive(Debug)]
+struct Request {
+    method: String,
+    path: String,
+    body: String,
+}
+
+#[derive(Debug)]
+struct Response {
+    status: u16,
+    body: Json,
+}
+
+impl Response {
+    fn new(status: u16, body: Json) -> Self {
+        Self { status, body }
+    }
+
+    fn error(status: u16, message: &str) -> Self {
+        Self::new(status, Json::object([("error", Json::from(message))]))
+    }
+
+    fn reason(&self) -> &'static str {
+        match self.status {
+            200 => "OK",
+            400 => "Bad Request",
+            404 => "Not Found",
+            405 => "Method Not Allowed",
+            413 => "Payload Too Large",
+            422 => "Unprocessable Entity",
+            _ => "Internal Server Error",
+        }
+    }
+}
+
+/// A small HTTP server exposing the solvers as a JSON API:
+///
+/// - `GET /days` lists the available days and parts.
+/// - `POST /days/{day}/parts/{part}` solves the part for the input in the request body.
+#[derive(Debug)]
+pub struct Server {
+    listener: TcpListener,
+    options: Options,
+}
+
+impl Server {
+    /// Requests that run over the timeout are answered with a timeout error.
+    pub fn bind(addr: impl ToSocketAddrs, timeout: Option<Duration>) -> io::Result<Self> {
+        Ok(Self {
+            listener: TcpListener::bind(addr)?,
+            options: Options {
+                timeout,
+                ..Options::default()
+            },
+        })
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Handles connections until the listener fails, one thread per connection.
+    pub fn serve(&self) -> io::Result<()> {
+        for stream in self.listener.incoming() {
+            let stream = stream?;
+            let options = self.options.clone();
+            thread::spawn(move || {
+                // the client went away or sent garbage, there is nobody to report that to
+                let _ = handle(stream, &options);
+            });
+        }
+        Ok(())
+    }
+}
+
+fn handle(mut stream: TcpStream, options: &Options) -> io::Result<()> {
+    let response = match read_request(&mut stream) {
+        Ok(Ok(request)) => route(&request, options),
+        Ok(Err(response)) => response,
+        Err(err) => return Err(err),
+    };
+    let body = response.body.to_string();
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        response.status,
+        response.reason(),
+        body.len(),
+        body
+    )?;
+    stream.flush()
+}
+
+fn read_request(stream: &mut TcpStream) -> io::Result<Result<Request, Response>> {
+    let mut reader = BufReader::new(stream);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    let mut parts = request_line.split_whitespace();
+    let (method, path) = match (parts.next(), parts.next()) {
+        (Some(method), Some(path)) => (method.to_owned(), path.to_owned()),
+        _ => return Ok(Err(Response::error(400, "malformed request line"))),
+    };
+    let mut content_length = 0;
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header)? == 0 || header.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("content-length") {
+                content_length = match value.trim().parse() {
+                    Ok(length) => length,
+                    Err(_) => return Ok(Err(Response::error(400, "invalid content length"))),
+                };
+            }
+        }
+    }
+    if content_length > MAX_BODY {
+        return Ok(Err(Response::error(413, "input too large")));
+    }
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body)?;
+    match String::from_utf8(body) {
+        Ok(body) => Ok(Ok(Request { method, path, body })),
+        Err(_) => Ok(Err(Response::error(400, "input is not valid UTF-8"))),
+    }
+}
+
+fn route(request: &Request, options: &Options) -> Response {
+    let segments: Vec<&str> = request
+        .path
+        .split('?')
+        .next()
+        .unwrap_or("")
+        .split('/')
+        .filter(|segment| !segment.is_empty())
+        .collect();
+    match (request.method.as_str(), segments.as_slice()) {
+        ("GET", ["days"]) => list_days(),
+        ("POST", ["days", day, "parts", part]) => solve(day, part, &request.body, options),
+        (_, ["days"]) | (_, ["days", _, "parts", _]) => Response::error(405, "method not allowed"),
+        _ => Response::error(404, "not found"),
+    }
+}
+
+fn list_days() -> Response {
+    let days = SOLVERS
+        .iter()
+        .map(|solver| {
+            Json::object([
+                ("day", Json::from(solver.day)),
+                ("parts", Json::from(vec![1u8, 2])),
+                ("version", Json::Number(solver.version as f64)),
+            ])
+        })
+        .collect();
+    Response::new(200, Json::object([("days", Json::Array(days))]))
+}
+
+fn solve(day: &str, part: &str, input: &str, options: &Options) -> Response {
+    let solver = match day.parse().ok().and_then(solvers::solver) {
+        Some(solver) => solver,
+        None => return Response::error(404, "unknown day"),
+    };
+    let part = match part.parse() {
+        Ok(part @ 1..=2) => part,
+        _ => return Response::error(404, "unknown part"),
+    };
+    let outcome = runner::solve_part(solver, "request", part, input, None, options);
+    let diagnostics = match &outcome.answer {
+        Err(SolveError::Parse(errors)) => errors
+            .iter()
+            .map(|error| {
+                Json::object([
+                    ("line", Json::from(error.line)),
+                    ("column", Json::from(error.column)),
+                    ("message", Json::from(error.message.as_str())),
+                ])
+            })
+            .collect(),
+        _ => Vec::new(),
+    };
+    let mut fields = vec![
+        ("day", Json::from(solver.day)),
+        ("part", Json::from(part)),
+        (
+            "time_ms",
+            Json::from(outcome.elapsed.as_secs_f64() * 1000.0),
+        ),
+        ("diagnostics", Json::Array(diagnostics)),
+    ];
+    match outcome.answer {
+        Ok(answer) => {
+            fields.push(("answer", Json::from(answer)));
+            Response::new(200, Json::object(fields))
+        }
+        Err(err) => {
+            fields.push(("error", Json::from(err.to_string())));
+            Response::new(422, Json::object(fields))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(addr: SocketAddr, method: &str, path: &str, body: &str) -> (u16, String) {
+        let mut stream = TcpStream::connect(addr).unwrap();
+        write!(
+            stream,
+            "{} {} HTTP/1.1\r\nHost: localhost\r\nContent-Length: {}\r\n\r\n{}",
+            method,
+            path,
+            body.len(),
+            body
+        )
+        .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        let (head, body) = response.split_once("\r\n\r\n").unwrap();
+        let status = head.split_whitespace().nth(1).unwrap().parse().unwrap();
+        (status, body.to_owned())
+    }
+
+    #[test]
+    fn test_server() {
+        let server = Server::bind("127.0.0.1:0", Some(Duration::from_millis(100))).unwrap();
+        let addr = server.local_addr().unwrap();
+        thread::spawn(move || server.serve());
+
+        let (status, body) = request(addr, "GET", "/days", "");
+        assert_eq!(status, 200);
+        assert!(body.starts_with(r#"{"days":[{"day":1,"parts":[1,2],"version":2},"#));
+
+        let (status, body) = request(addr, "POST", "/days/1/parts/2", "1\n2\n3\n4\n");
+        assert_eq!(status, 200);
+        assert!(body.contains(r#""diagnostics":[],"answer":"1"}"#));
+
+        let (status, body) = request(addr, "POST", "/days/1/parts/1", "1\nx\n");
+        assert_eq!(status, 422);
+        assert!(body.contains(
+            r#""diagnostics":[{"line":2,"column":1,"message":"expected an integer, found \"x\""}]"#
+        ));
+
+        let (status, body) = request(addr, "POST", "/days/7/parts/1", "0,4000000000");
+        assert_eq!(status, 422);
+        assert!(body.ends_with(r#""error":"timed out"}"#));
+
+        assert_eq!(request(addr, "GET", "/days/1/parts/1", "").0, 405);
+        assert_eq!(request(addr, "POST", "/days/42/parts/1", "").0, 404);
+        assert_eq!(request(addr, "GET", "/nope", "").0, 404);
+    }
+}
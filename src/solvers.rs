@@ -0,0 +1,257 @@
+use crate::plugin::Plugin;
+use crate::utils::arith::Overflow;
+use crate::utils::cancel::{CancelToken, Cancelled};
+use crate::utils::parse::ParseError;
+use crate::utils::stream::StreamError;
+use crate::{day1, day10, day2, day3, day4, day5, day6, day7, day8, day9};
+use std::fmt;
+use std::io::BufRead;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SolveError {
+    Parse(Vec<ParseError>),
+    Overflow,
+    Timeout,
+    Io(String),
+    Panic(String),
+    Plugin(String),
+}
+
+impl fmt::Display for SolveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SolveError::Parse(errors) => match errors.as_slice() {
+                [error] => write!(f, "parse error: {}", error),
+                [first, ..] => write!(f, "parse error: {} (and {} more)", first, errors.len() - 1),
+                [] => write!(f, "parse error"),
+            },
+            SolveError::Overflow => write!(f, "{}", Overflow),
+            SolveError::Timeout => write!(f, "{}", Cancelled),
+            SolveError::Io(message) => write!(f, "could not read input: {}", message),
+            SolveError::Panic(message) => write!(f, "solver panicked: {}", message),
+            SolveError::Plugin(message) => write!(f, "plugin failed: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for SolveError {}
+
+impl From<ParseError> for SolveError {
+    fn from(error: ParseError) -> Self {
+        SolveError::Parse(vec![error])
+    }
+}
+
+impl From<Overflow> for SolveError {
+    fn from(_: Overflow) -> Self {
+        SolveError::Overflow
+    }
+}
+
+impl From<Cancelled> for SolveError {
+    fn from(_: Cancelled) -> Self {
+        SolveError::Timeout
+    }
+}
+
+impl From<StreamError> for SolveError {
+    fn from(err: StreamError) -> Self {
+        match err {
+            StreamError::Io(err) => SolveError::Io(err.to_string()),
+            StreamError::Parse(err) => err.into(),
+            StreamError::Cancelled => SolveError::Timeout,
+        }
+    }
+}
+
+pub type Part = fn(&str, &CancelToken) -> Result<String, SolveError>;
+pub type StreamPart = fn(&mut dyn BufRead, &CancelToken) -> Result<String, SolveError>;
+pub type Render = fn(&str) -> Result<String, SolveError>;
+
+#[derive(Debug, Clone)]
+pub struct Solver {
+    pub day: u8,
+    /// Bump whenever the solver's answers could change, so cached answers are not reused.
+    pub version: u32,
+    /// The puzzle input bundled with the binary.
+    pub input: &'static str,
+    pub lint: fn(&str) -> Vec<ParseError>,
+    pub parts: [Part; 2],
+    /// An external executable that solves the day instead of `parts`.
+    pub plugin: Option<Plugin>,
+    /// Solves a part reading the input a line at a time, for days whose algorithm allows it.
+    pub streams: [Option<StreamPart>; 2],
+    /// Draws the input as an SVG, for days that work on a grid.
+    pub render: Option<Render>,
+    /// Draws the input as text, for days that work on a grid.
+    pub grid: Option<Render>,
+    /// Lists the intermediate results that lead to the answers, one per line.
+    pub trace: Option<Render>,
+}
+
+impl Solver {
+    /// Checks the input against the day's rules, then solves the given part (1 or 2).
+    pub fn solve(&self, part: u8, input: &str, cancel: &CancelToken) -> Result<String, SolveError> {
+        let errors = (self.lint)(input);
+        if !errors.is_empty() {
+            return Err(SolveError::Parse(errors));
+        }
+        match &self.plugin {
+            Some(plugin) => plugin.solve(self.day, part, input, cancel),
+            None => (self.parts[part as usize - 1])(input, cancel),
+        }
+    }
+
+    /// This day solved by an external executable, see `plugin`. It cannot stream its input.
+    pub fn with_plugin(&self, plugin: Plugin) -> Self {
+        Self {
+            plugin: Some(plugin),
+            streams: [None, None],
+            ..self.clone()
+        }
+    }
+}
+
+pub static SOLVERS: [Solver; 10] = [
+    Solver {
+        day: 1,
+        version: 2,
+        input: include_str!("data/1"),
+        lint: day1::lint,
+        parts: [day1::part1, day1::part2],
+        plugin: None,
+        streams: [Some(day1::stream_part1), Some(day1::stream_part2)],
+        render: None,
+        grid: None,
+        trace: None,
+    },
+    Solver {
+        day: 2,
+        version: 2,
+        input: include_str!("data/2"),
+        lint: day2::lint,
+        parts: [day2::part1, day2::part2],
+        plugin: None,
+        streams: [Some(day2::stream_part1), Some(day2::stream_part2)],
+        render: None,
+        grid: None,
+        trace: None,
+    },
+    Solver {
+        day: 3,
+        version: 2,
+        input: include_str!("data/3"),
+        lint: day3::lint,
+        parts: [day3::part1, day3::part2],
+        plugin: None,
+        streams: [Some(day3::stream_part1), None],
+        render: None,
+        grid: None,
+        trace: None,
+    },
+    Solver {
+        day: 4,
+        version: 2,
+        input: include_str!("data/4"),
+        lint: day4::lint,
+        parts: [day4::part1, day4::part2],
+        plugin: None,
+        streams: [None, None],
+        render: None,
+        grid: None,
+        trace: Some(day4::trace),
+    },
+    Solver {
+        day: 5,
+        version: 1,
+        input: include_str!("data/5"),
+        lint: day5::lint,
+        parts: [day5::part1, day5::part2],
+        plugin: None,
+        streams: [None, None],
+        render: Some(day5::render),
+        grid: Some(day5::grid),
+        trace: None,
+    },
+    Solver {
+        day: 6,
+        version: 2,
+        input: include_str!("data/6"),
+        lint: day6::lint,
+        parts: [day6::part1, day6::part2],
+        plugin: None,
+        streams: [None, None],
+        render: None,
+        grid: None,
+        trace: None,
+    },
+    Solver {
+        day: 7,
+        version: 2,
+        input: include_str!("data/7"),
+        lint: day7::lint,
+        parts: [day7::part1, day7::part2],
+        plugin: None,
+        streams: [None, None],
+        render: None,
+        grid: None,
+        trace: None,
+    },
+    Solver {
+        day: 8,
+        version: 2,
+        input: include_str!("data/8"),
+        lint: day8::lint,
+        parts: [day8::part1, day8::part2],
+        plugin: None,
+        streams: [None, None],
+        render: None,
+        grid: None,
+        trace: Some(day8::trace),
+    },
+    Solver {
+        day: 9,
+        version: 1,
+        input: include_str!("data/9"),
+        lint: day9::lint,
+        parts: [day9::part1, day9::part2],
+        plugin: None,
+        streams: [None, None],
+        render: Some(day9::render),
+        grid: Some(day9::grid),
+        trace: Some(day9::trace),
+    },
+    Solver {
+        day: 10,
+        version: 1,
+        input: include_str!("data/10"),
+        lint: day10::lint,
+        parts: [day10::part1, day10::part2],
+        plugin: None,
+        streams: [Some(day10::stream_part1), None],
+        render: None,
+        grid: None,
+        trace: Some(day10::trace),
+    },
+];
+
+pub fn solver(day: u8) -> Option<&'static Solver> {
+    SOLVERS.iter().find(|solver| solver.day == day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_solve() {
+        let solver = solver(1).unwrap();
+        let cancel = CancelToken::new();
+        assert_eq!(solver.solve(1, "1\n2\n1\n3", &cancel), Ok("2".to_owned()));
+        assert_eq!(solver.solve(2, "1\n2\n1\n3", &cancel), Ok("1".to_owned()));
+        assert!(matches!(
+            solver.solve(1, "1\nx\n3", &cancel),
+            Err(SolveError::Parse(errors)) if errors.len() == 1
+        ));
+    }
+}
@@ -1,3 +1,13 @@
+pub mod arith;
+pub mod cancel;
+pub mod json;
+pub mod parse;
+#[cfg(test)]
+pub mod snapshot;
+pub mod stream;
+pub mod svg;
+pub mod terminal;
+
 use num_traits::{CheckedAdd, CheckedSub, One};
 use std::collections::HashMap;
 use std::hash::Hash;
@@ -0,0 +1,91 @@
+use num_traits::{CheckedAdd, CheckedMul, CheckedSub, FromPrimitive, One, Zero};
+use std::fmt;
+
+/// Returned instead of a wrong answer when a result does not fit the chosen integer type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Overflow;
+
+impl fmt::Display for Overflow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "arithmetic overflow, try a wider integer type")
+    }
+}
+
+impl std::error::Error for Overflow {}
+
+/// The integer types solvers can count in, such as `u64`, `u128` or `num_bigint::BigUint`.
+pub trait Integer:
+    Clone
+    + Ord
+    + fmt::Debug
+    + fmt::Display
+    + Zero
+    + One
+    + CheckedAdd
+    + CheckedSub
+    + CheckedMul
+    + FromPrimitive
+{
+}
+
+impl<T> Integer for T where
+    T: Clone
+        + Ord
+        + fmt::Debug
+        + fmt::Display
+        + Zero
+        + One
+        + CheckedAdd
+        + CheckedSub
+        + CheckedMul
+        + FromPrimitive
+{
+}
+
+pub fn add<N: Integer>(lhs: &N, rhs: &N) -> Result<N, Overflow> {
+    lhs.checked_add(rhs).ok_or(Overflow)
+}
+
+pub fn mul<N: Integer>(lhs: &N, rhs: &N) -> Result<N, Overflow> {
+    lhs.checked_mul(rhs).ok_or(Overflow)
+}
+
+pub fn from_u64<N: Integer>(n: u64) -> Result<N, Overflow> {
+    N::from_u64(n).ok_or(Overflow)
+}
+
+pub fn sum<N: Integer>(
+    values: impl IntoIterator<Item = Result<N, Overflow>>,
+) -> Result<N, Overflow> {
+    values
+        .into_iter()
+        .try_fold(N::zero(), |total, value| add(&total, &value?))
+}
+
+/// Formats either the result or the overflow error, for printing answers.
+pub fn display<N: Integer>(result: Result<N, Overflow>) -> String {
+    result.map_or_else(|err| err.to_string(), |n| n.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use num_bigint::BigUint;
+
+    #[test]
+    fn test_sum() {
+        assert_eq!(sum([Ok(1u8), Ok(2), Ok(3)]), Ok(6));
+        assert_eq!(sum([Ok(200u8), Ok(100)]), Err(Overflow));
+        assert_eq!(sum([Ok(1u8), Err(Overflow)]), Err(Overflow));
+    }
+
+    #[test]
+    fn test_big() {
+        let big: BigUint = from_u64(u64::MAX).unwrap();
+        assert_eq!(
+            mul(&big, &big).unwrap().to_string(),
+            "340282366920938463426481119284349108225"
+        );
+        assert_eq!(mul(&u64::MAX, &2), Err(Overflow));
+    }
+}
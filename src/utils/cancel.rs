@@ -0,0 +1,141 @@
+use std::fmt;
+use std::panic::RefUnwindSafe;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Returned by a solver that gave up because its token was cancelled or its time ran out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cancelled;
+
+impl fmt::Display for Cancelled {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "timed out")
+    }
+}
+
+impl std::error::Error for Cancelled {}
+
+type Report = dyn Fn(u64, u64) + Send + Sync + RefUnwindSafe;
+
+/// Checked by long running solvers in their main loops, so they can be stopped cooperatively
+/// and tell how far along they are.
+#[derive(Clone, Default)]
+pub struct CancelToken {
+    deadline: Option<Instant>,
+    cancelled: Arc<AtomicBool>,
+    progress: Option<Arc<Report>>,
+    /// The last percentage reported plus one, zero before the first report.
+    reported: Arc<AtomicU64>,
+}
+
+impl fmt::Debug for CancelToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CancelToken")
+            .field("deadline", &self.deadline)
+            .field("cancelled", &self.cancelled)
+            .field("progress", &self.progress.is_some())
+            .finish()
+    }
+}
+
+impl CancelToken {
+    /// A token that is only cancelled by calling `cancel`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A token that cancels itself once the timeout has passed.
+    pub fn with_timeout(timeout: Duration) -> Self {
+        Self {
+            deadline: Some(Instant::now() + timeout),
+            ..Self::default()
+        }
+    }
+
+    /// Passes progress on to `report` as (step, total), at most once per percent.
+    pub fn with_progress(
+        self,
+        report: impl Fn(u64, u64) + Send + Sync + RefUnwindSafe + 'static,
+    ) -> Self {
+        Self {
+            progress: Some(Arc::new(report)),
+            ..self
+        }
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+            || self
+                .deadline
+                .is_some_and(|deadline| Instant::now() >= deadline)
+    }
+
+    pub fn check(&self) -> Result<(), Cancelled> {
+        if self.is_cancelled() {
+            Err(Cancelled)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Reports that `step` out of `total` steps are done and checks for cancellation, meant to
+    /// be called on every step of a main loop.
+    pub fn progress(&self, step: u64, total: u64) -> Result<(), Cancelled> {
+        if let Some(report) = &self.progress {
+            let percent = (step * 100).checked_div(total).unwrap_or(100);
+            if self.reported.swap(percent + 1, Ordering::Relaxed) != percent + 1 {
+                report(step, total);
+            }
+        }
+        self.check()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cancel() {
+        let token = CancelToken::new();
+        let clone = token.clone();
+        assert_eq!(clone.check(), Ok(()));
+        token.cancel();
+        assert_eq!(clone.check(), Err(Cancelled));
+    }
+
+    #[test]
+    fn test_progress() {
+        let reports = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let token = CancelToken::new().with_progress({
+            let reports = reports.clone();
+            move |step, total| reports.lock().unwrap().push((step, total))
+        });
+        for step in 0..=1000 {
+            assert_eq!(token.progress(step, 1000), Ok(()));
+        }
+        let reports = reports.lock().unwrap();
+        assert_eq!(reports.len(), 101);
+        assert_eq!(reports[..3], [(0, 1000), (10, 1000), (20, 1000)]);
+        assert_eq!(reports.last(), Some(&(1000, 1000)));
+        token.cancel();
+        assert_eq!(token.progress(0, 0), Err(Cancelled));
+    }
+
+    #[test]
+    fn test_timeout() {
+        assert_eq!(
+            CancelToken::with_timeout(Duration::from_secs(60)).check(),
+            Ok(())
+        );
+        assert_eq!(
+            CancelToken::with_timeout(Duration::ZERO).check(),
+            Err(Cancelled)
+        );
+    }
+}
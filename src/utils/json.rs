@@ -0,0 +1,359 @@
+use crate::utils::parse::{ParseError, ParseResult, Span};
+use std::fmt;
+use std::str::FromStr;
+
+/// Just enough JSON to talk to other tools, without pulling in a serialisation framework.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Json {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    pub fn object<K: Into<String>>(fields: impl IntoIterator<Item = (K, Json)>) -> Self {
+        Json::Object(
+            fields
+                .into_iter()
+                .map(|(key, value)| (key.into(), value))
+                .collect(),
+        )
+    }
+
+    /// The value of a field, if this is an object that has it.
+    pub fn get(&self, key: &str) -> Option<&Json> {
+        match self {
+            Json::Object(fields) => fields
+                .iter()
+                .find(|(name, _)| name == key)
+                .map(|(_, value)| value),
+            _ => None,
+        }
+    }
+}
+
+impl From<&str> for Json {
+    fn from(s: &str) -> Self {
+        Json::String(s.to_owned())
+    }
+}
+
+impl From<String> for Json {
+    fn from(s: String) -> Self {
+        Json::String(s)
+    }
+}
+
+impl From<bool> for Json {
+    fn from(b: bool) -> Self {
+        Json::Bool(b)
+    }
+}
+
+impl From<u8> for Json {
+    fn from(n: u8) -> Self {
+        Json::Number(n as f64)
+    }
+}
+
+impl From<usize> for Json {
+    fn from(n: usize) -> Self {
+        Json::Number(n as f64)
+    }
+}
+
+impl From<u64> for Json {
+    fn from(n: u64) -> Self {
+        Json::Number(n as f64)
+    }
+}
+
+impl From<f64> for Json {
+    fn from(n: f64) -> Self {
+        Json::Number(n)
+    }
+}
+
+impl<T: Into<Json>> From<Option<T>> for Json {
+    fn from(value: Option<T>) -> Self {
+        value.map_or(Json::Null, Into::into)
+    }
+}
+
+impl<T: Into<Json>> From<Vec<T>> for Json {
+    fn from(values: Vec<T>) -> Self {
+        Json::Array(values.into_iter().map(Into::into).collect())
+    }
+}
+
+fn write_string(f: &mut fmt::Formatter<'_>, s: &str) -> fmt::Result {
+    write!(f, "\"")?;
+    for c in s.chars() {
+        match c {
+            '"' => write!(f, "\\\"")?,
+            '\\' => write!(f, "\\\\")?,
+            '\n' => write!(f, "\\n")?,
+            '\r' => write!(f, "\\r")?,
+            '\t' => write!(f, "\\t")?,
+            c if (c as u32) < 0x20 => write!(f, "\\u{:04x}", c as u32)?,
+            c => write!(f, "{}", c)?,
+        }
+    }
+    write!(f, "\"")
+}
+
+impl fmt::Display for Json {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Json::Null => write!(f, "null"),
+            Json::Bool(b) => write!(f, "{}", b),
+            Json::Number(n) if n.is_finite() => write!(f, "{}", n),
+            Json::Number(_) => write!(f, "null"),
+            Json::String(s) => write_string(f, s),
+            Json::Array(values) => {
+                write!(f, "[")?;
+                for (index, value) in values.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{}", value)?;
+                }
+                write!(f, "]")
+            }
+            Json::Object(fields) => {
+                write!(f, "{{")?;
+                for (index, (key, value)) in fields.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ",")?;
+                    }
+                    write_string(f, key)?;
+                    write!(f, ":{}", value)?;
+                }
+                write!(f, "}}")
+            }
+        }
+    }
+}
+
+/// A recursive descent parser, consuming the input from the front.
+struct Parser<'a> {
+    rest: Span<'a>,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<char> {
+        self.rest.as_str().chars().next()
+    }
+
+    fn advance(&mut self, len: usize) {
+        self.rest = self.rest.split_at(len).1;
+    }
+
+    fn skip_whitespace(&mut self) {
+        let text = self.rest.as_str();
+        self.advance(text.len() - text.trim_start().len());
+    }
+
+    fn expect(&mut self, token: &str) -> ParseResult<()> {
+        if self.rest.as_str().starts_with(token) {
+            self.advance(token.len());
+            Ok(())
+        } else {
+            Err(self.rest.error(format!("expected {:?}", token)))
+        }
+    }
+
+    fn value(&mut self) -> ParseResult<Json> {
+        self.skip_whitespace();
+        let value = match self.peek() {
+            Some('n') => self.expect("null").map(|_| Json::Null),
+            Some('t') => self.expect("true").map(|_| Json::Bool(true)),
+            Some('f') => self.expect("false").map(|_| Json::Bool(false)),
+            Some('"') => self.string().map(Json::String),
+            Some('[') => self.array(),
+            Some('{') => self.object(),
+            Some(c) if c == '-' || c.is_ascii_digit() => self.number(),
+            _ => Err(self.rest.error("expected a value")),
+        }?;
+        self.skip_whitespace();
+        Ok(value)
+    }
+
+    fn number(&mut self) -> ParseResult<Json> {
+        let text = self.rest.as_str();
+        let len = text
+            .find(|c: char| !matches!(c, '0'..='9' | '-' | '+' | '.' | 'e' | 'E'))
+            .unwrap_or(text.len());
+        let (number, _) = self.rest.split_at(len);
+        let value = number
+            .as_str()
+            .parse()
+            .map_err(|_| number.error(format!("invalid number {:?}", number.as_str())))?;
+        self.advance(len);
+        Ok(Json::Number(value))
+    }
+
+    fn string(&mut self) -> ParseResult<String> {
+        let start = self.rest;
+        self.expect("\"")?;
+        let mut value = String::new();
+        loop {
+            let c = self
+                .peek()
+                .ok_or_else(|| start.error("unterminated string"))?;
+            self.advance(c.len_utf8());
+            match c {
+                '"' => return Ok(value),
+                '\\' => value.push(self.escape()?),
+                c => value.push(c),
+            }
+        }
+    }
+
+    fn escape(&mut self) -> ParseResult<char> {
+        let start = self.rest;
+        let c = self
+            .peek()
+            .ok_or_else(|| start.error("unterminated escape"))?;
+        self.advance(c.len_utf8());
+        Ok(match c {
+            '"' | '\\' | '/' => c,
+            'b' => '\u{8}',
+            'f' => '\u{c}',
+            'n' => '\n',
+            'r' => '\r',
+            't' => '\t',
+            'u' => {
+                let high = self.hex()?;
+                let code = if (0xd800..0xdc00).contains(&high) {
+                    self.expect("\\u")?;
+                    match self.hex()? {
+                        low @ 0xdc00..=0xdfff => 0x10000 + ((high - 0xd800) << 10) + (low - 0xdc00),
+                        _ => return Err(start.error("unpaired surrogate")),
+                    }
+                } else {
+                    high
+                };
+                char::from_u32(code).ok_or_else(|| start.error("invalid unicode escape"))?
+            }
+            c => return Err(start.error(format!("unknown escape \\{}", c))),
+        })
+    }
+
+    fn hex(&mut self) -> ParseResult<u32> {
+        let digits = self.rest.as_str().get(..4).unwrap_or_default();
+        if digits.len() != 4 || !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(self.rest.error("expected 4 hex digits"));
+        }
+        self.advance(4);
+        Ok(u32::from_str_radix(digits, 16).unwrap())
+    }
+
+    fn array(&mut self) -> ParseResult<Json> {
+        self.expect("[")?;
+        self.skip_whitespace();
+        let mut values = Vec::new();
+        if self.expect("]").is_ok() {
+            return Ok(Json::Array(values));
+        }
+        loop {
+            values.push(self.value()?);
+            match self.peek() {
+                Some(',') => self.advance(1),
+                Some(']') => {
+                    self.advance(1);
+                    return Ok(Json::Array(values));
+                }
+                _ => return Err(self.rest.error("expected ',' or ']'")),
+            }
+        }
+    }
+
+    fn object(&mut self) -> ParseResult<Json> {
+        self.expect("{")?;
+        self.skip_whitespace();
+        let mut fields = Vec::new();
+        if self.expect("}").is_ok() {
+            return Ok(Json::Object(fields));
+        }
+        loop {
+            self.skip_whitespace();
+            let key = self.string()?;
+            self.skip_whitespace();
+            self.expect(":")?;
+            fields.push((key, self.value()?));
+            match self.peek() {
+                Some(',') => self.advance(1),
+                Some('}') => {
+                    self.advance(1);
+                    return Ok(Json::Object(fields));
+                }
+                _ => return Err(self.rest.error("expected ',' or '}'")),
+            }
+        }
+    }
+}
+
+impl FromStr for Json {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser { rest: Span::new(s) };
+        let value = parser.value()?;
+        if !parser.rest.is_empty() {
+            return Err(parser.rest.error("expected the end of the input"));
+        }
+        Ok(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display() {
+        let json = Json::object([
+            ("day", Json::from(1u8)),
+            ("answer", Json::from("a \"quoted\"\nline")),
+            ("time", Json::from(0.5)),
+            ("missing", Json::from(None::<String>)),
+            ("list", Json::from(vec![true, false])),
+        ]);
+        assert_eq!(
+            json.to_string(),
+            r#"{"day":1,"answer":"a \"quoted\"\nline","time":0.5,"missing":null,"list":[true,false]}"#
+        );
+    }
+
+    #[test]
+    fn test_parse() {
+        let json: Json = r#" {"part1": "42", "part2": [1.5, -2e3, true, null],
+            "text": "a\"\n\u00e9\ud83d\ude00", "empty": {}} "#
+            .parse()
+            .unwrap();
+        assert_eq!(json.get("part1"), Some(&Json::from("42")));
+        assert_eq!(
+            json.get("part2"),
+            Some(&Json::Array(vec![
+                Json::Number(1.5),
+                Json::Number(-2000.0),
+                Json::Bool(true),
+                Json::Null
+            ]))
+        );
+        assert_eq!(json.get("text"), Some(&Json::from("a\"\n\u{e9}\u{1f600}")));
+        assert_eq!(json.get("empty"), Some(&Json::Object(Vec::new())));
+        assert_eq!(json.get("missing"), None);
+        let err = "{\"a\": 1,\n \"b\" 2}".parse::<Json>().unwrap_err();
+        assert_eq!(
+            (err.line, err.column, err.message.as_str()),
+            (2, 6, "expected \":\"")
+        );
+        assert!("[1] x".parse::<Json>().is_err());
+        assert!("\"open".parse::<Json>().is_err());
+    }
+}
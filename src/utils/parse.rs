@@ -0,0 +1,338 @@
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: {}",
+            self.line, self.column, self.message
+        )
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+pub type ParseResult<T> = Result<T, ParseError>;
+
+/// A slice of the input that remembers where it came from, so errors can point at it.
+#[derive(Debug, Clone, Copy)]
+pub struct Span<'a> {
+    source: &'a str,
+    start: usize,
+    end: usize,
+}
+
+impl<'a> Span<'a> {
+    pub fn new(source: &'a str) -> Self {
+        Self {
+            source,
+            start: 0,
+            end: source.len(),
+        }
+    }
+
+    pub fn as_str(&self) -> &'a str {
+        &self.source[self.start..self.end]
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+
+    /// The 1-based line and column of the start of this span in the original input.
+    pub fn position(&self) -> (usize, usize) {
+        let before = &self.source[..self.start];
+        let line = before.matches('\n').count() + 1;
+        let line_start = before.rfind('\n').map(|index| index + 1).unwrap_or(0);
+        (
+            line,
+            self.source[line_start..self.start].chars().count() + 1,
+        )
+    }
+
+    pub fn error(&self, message: impl Into<String>) -> ParseError {
+        let (line, column) = self.position();
+        ParseError {
+            line,
+            column,
+            message: message.into(),
+        }
+    }
+
+    fn slice(&self, start: usize, end: usize) -> Self {
+        Self {
+            source: self.source,
+            start: self.start + start,
+            end: self.start + end,
+        }
+    }
+
+    /// The empty span just past the end of this one, used to report missing input.
+    fn end(&self) -> Self {
+        let len = self.end - self.start;
+        self.slice(len, len)
+    }
+
+    pub fn trim(&self) -> Self {
+        let text = self.as_str();
+        let start = text.len() - text.trim_start().len();
+        self.slice(start, text.trim_end().len().max(start))
+    }
+
+    /// Splits the span at a byte offset, which must be on a character boundary.
+    pub fn split_at(&self, mid: usize) -> (Self, Self) {
+        (self.slice(0, mid), self.slice(mid, self.end - self.start))
+    }
+
+    pub fn split_once(&self, separator: &str) -> Option<(Self, Self)> {
+        self.as_str().find(separator).map(|index| {
+            (
+                self.slice(0, index),
+                self.slice(index + separator.len(), self.end - self.start),
+            )
+        })
+    }
+
+    pub fn chars(&self) -> impl Iterator<Item = (Self, char)> + 'a {
+        let span = *self;
+        self.as_str()
+            .char_indices()
+            .map(move |(index, c)| (span.slice(index, index + c.len_utf8()), c))
+    }
+
+    /// Splits the span into tokens according to the separator.
+    pub fn split(&self, separator: Separator<'a>) -> Vec<Self> {
+        let text = self.as_str();
+        match separator {
+            Separator::Str(separator) => {
+                let mut tokens = Vec::new();
+                let mut start = 0;
+                for (index, _) in text.match_indices(separator) {
+                    tokens.push(self.slice(start, index));
+                    start = index + separator.len();
+                }
+                tokens.push(self.slice(start, text.len()));
+                tokens
+            }
+            Separator::Whitespace => {
+                let mut tokens = Vec::new();
+                let mut start = None;
+                for (index, c) in text.char_indices() {
+                    match (c.is_whitespace(), start) {
+                        (true, Some(token_start)) => {
+                            tokens.push(self.slice(token_start, index));
+                            start = None;
+                        }
+                        (false, None) => start = Some(index),
+                        _ => (),
+                    }
+                }
+                if let Some(token_start) = start {
+                    tokens.push(self.slice(token_start, text.len()));
+                }
+                tokens
+            }
+            Separator::Lines => {
+                let mut lines = self.split(Separator::Str("\n"));
+                if lines.len() > 1 && lines.last().map(Span::is_empty).unwrap_or(false) {
+                    lines.pop();
+                }
+                lines
+                    .into_iter()
+                    .map(|line| match line.as_str().strip_suffix('\r') {
+                        Some(stripped) => line.slice(0, stripped.len()),
+                        None => line,
+                    })
+                    .collect()
+            }
+            Separator::BlankLines => {
+                let mut blocks = Vec::new();
+                let mut block: Option<Self> = None;
+                for line in self.split(Separator::Lines) {
+                    if line.as_str().trim().is_empty() {
+                        blocks.extend(block.take());
+                    } else {
+                        block = Some(match block {
+                            Some(block) => Self {
+                                end: line.end,
+                                ..block
+                            },
+                            None => line,
+                        });
+                    }
+                }
+                blocks.extend(block);
+                blocks
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum Separator<'a> {
+    /// A literal separator such as `","` or `" -> "`.
+    Str(&'a str),
+    /// One or more whitespace characters, leading and trailing whitespace is ignored.
+    Whitespace,
+    /// Line breaks, a trailing newline does not produce an empty last line.
+    Lines,
+    /// One or more blank lines, used for inputs made up of blocks.
+    BlankLines,
+}
+
+/// Parses the whole span, ignoring surrounding whitespace, as an integer.
+pub fn integer<T: FromStr>(span: Span) -> ParseResult<T> {
+    let span = span.trim();
+    span.as_str()
+        .parse()
+        .map_err(|_| span.error(format!("expected an integer, found {:?}", span.as_str())))
+}
+
+/// Parses every token of the span with `item`.
+pub fn separated<'a, T>(
+    separator: Separator<'a>,
+    item: impl Fn(Span<'a>) -> ParseResult<T>,
+) -> impl Fn(Span<'a>) -> ParseResult<Vec<T>> {
+    move |span| span.split(separator).into_iter().map(&item).collect()
+}
+
+/// Parses exactly `N` tokens of the span with `item`.
+pub fn array<'a, T, const N: usize>(
+    separator: Separator<'a>,
+    item: impl Fn(Span<'a>) -> ParseResult<T>,
+) -> impl Fn(Span<'a>) -> ParseResult<[T; N]> {
+    move |span| {
+        let tokens = span.split(separator);
+        if tokens.len() < N {
+            return Err(span
+                .end()
+                .error(format!("expected {} items, found {}", N, tokens.len())));
+        }
+        if let Some(extra) = tokens.get(N) {
+            return Err(extra.error(format!(
+                "expected {} items, found unexpected {:?}",
+                N,
+                extra.as_str()
+            )));
+        }
+        let items = tokens
+            .into_iter()
+            .map(&item)
+            .collect::<ParseResult<Vec<T>>>()?;
+        match items.try_into() {
+            Ok(items) => Ok(items),
+            Err(_) => unreachable!(),
+        }
+    }
+}
+
+/// Parses every blank line separated block of the span with `item`.
+pub fn blocks<'a, T>(
+    item: impl Fn(Span<'a>) -> ParseResult<T>,
+) -> impl Fn(Span<'a>) -> ParseResult<Vec<T>> {
+    separated(Separator::BlankLines, item)
+}
+
+/// Parses a `key<separator>value` token, such as `forward 5` or `0,9 -> 5,9`.
+pub fn key_value<'a, K, V>(
+    separator: &'a str,
+    key: impl Fn(Span<'a>) -> ParseResult<K>,
+    value: impl Fn(Span<'a>) -> ParseResult<V>,
+) -> impl Fn(Span<'a>) -> ParseResult<(K, V)> {
+    move |span| match span.split_once(separator) {
+        Some((k, v)) => Ok((key(k)?, value(v)?)),
+        None => Err(span.error(format!(
+            "expected {:?} separated pair, found {:?}",
+            separator,
+            span.as_str()
+        ))),
+    }
+}
+
+/// Checks every token of the span with `item` and collects all errors instead of stopping at
+/// the first one.
+pub fn lint<'a, T>(
+    span: Span<'a>,
+    separator: Separator<'a>,
+    item: impl Fn(Span<'a>) -> ParseResult<T>,
+) -> Vec<ParseError> {
+    span.split(separator)
+        .into_iter()
+        .flat_map(|token| item(token).err())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_integer() {
+        assert_eq!(integer::<u32>(Span::new(" 42 ")), Ok(42));
+        assert_eq!(
+            integer::<u32>(Span::new("x")),
+            Err(ParseError {
+                line: 1,
+                column: 1,
+                message: "expected an integer, found \"x\"".to_owned()
+            })
+        );
+    }
+
+    #[test]
+    fn test_separated() {
+        let parser = separated(Separator::Str(","), integer::<i32>);
+        assert_eq!(parser(Span::new("1,-2,3")), Ok(vec![1, -2, 3]));
+        let err = parser(Span::new("1,2,x")).unwrap_err();
+        assert_eq!((err.line, err.column), (1, 5));
+    }
+
+    #[test]
+    fn test_array() {
+        let parser = array::<_, 3>(Separator::Whitespace, integer::<u8>);
+        assert_eq!(parser(Span::new(" 1  2 3")), Ok([1, 2, 3]));
+        let err = parser(Span::new("1 2 3 4")).unwrap_err();
+        assert_eq!((err.line, err.column), (1, 7));
+        let err = parser(Span::new("1 2")).unwrap_err();
+        assert_eq!(err.message, "expected 3 items, found 2");
+    }
+
+    #[test]
+    fn test_blocks() {
+        let parser = blocks(separated(Separator::Lines, integer::<u8>));
+        assert_eq!(
+            parser(Span::new("1\n2\n\n3\n")),
+            Ok(vec![vec![1, 2], vec![3]])
+        );
+        let err = parser(Span::new("1\n2\n\n3\nx\n")).unwrap_err();
+        assert_eq!((err.line, err.column), (5, 1));
+    }
+
+    #[test]
+    fn test_key_value() {
+        let parser = key_value(" ", |span: Span| Ok(span.as_str()), integer::<i32>);
+        assert_eq!(parser(Span::new("forward 5")), Ok(("forward", 5)));
+        let err = parser(Span::new("forward")).unwrap_err();
+        assert_eq!(
+            err.message,
+            "expected \" \" separated pair, found \"forward\""
+        );
+    }
+
+    #[test]
+    fn test_lint() {
+        let errors = lint(Span::new("1\nx\n3\ny"), Separator::Lines, integer::<u8>);
+        assert_eq!(
+            errors.iter().map(|err| err.line).collect::<Vec<_>>(),
+            vec![2, 4]
+        );
+    }
+}
@@ -0,0 +1,137 @@
+//! Snapshot testing: intermediate results are rendered as text and compared against files under
+//! `src/snapshots`. Run the tests with `UPDATE_SNAPSHOTS=1` to write new or changed snapshots.
+
+use std::fs;
+use std::path::PathBuf;
+
+const CONTEXT: usize = 2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Change<'a> {
+    Same(&'a str),
+    Removed(&'a str),
+    Added(&'a str),
+}
+
+/// A line diff through the longest common subsequence, snapshots are small enough for that.
+fn changes<'a>(old: &[&'a str], new: &[&'a str]) -> Vec<Change<'a>> {
+    let mut common = vec![vec![0; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            common[i][j] = if old[i] == new[j] {
+                common[i + 1][j + 1] + 1
+            } else {
+                common[i + 1][j].max(common[i][j + 1])
+            };
+        }
+    }
+    let (mut i, mut j) = (0, 0);
+    let mut changes = Vec::new();
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            changes.push(Change::Same(old[i]));
+            i += 1;
+            j += 1;
+        } else if i < old.len() && (j == new.len() || common[i + 1][j] >= common[i][j + 1]) {
+            changes.push(Change::Removed(old[i]));
+            i += 1;
+        } else {
+            changes.push(Change::Added(new[j]));
+            j += 1;
+        }
+    }
+    changes
+}
+
+/// Renders the changed lines with a little context, `-` for the snapshot and `+` for the actual
+/// output, and `@@ line N @@` headers counting lines of the snapshot.
+pub fn diff(old: &str, new: &str) -> String {
+    let old = old.lines().collect::<Vec<_>>();
+    let new = new.lines().collect::<Vec<_>>();
+    let changes = changes(&old, &new);
+    let near_change = |index: usize| {
+        changes[index.saturating_sub(CONTEXT)..(index + CONTEXT + 1).min(changes.len())]
+            .iter()
+            .any(|change| !matches!(change, Change::Same(_)))
+    };
+    let mut diff = String::new();
+    let mut line = 1;
+    let mut skipped = true;
+    for (index, change) in changes.iter().enumerate() {
+        if near_change(index) {
+            if skipped {
+                diff.push_str(&format!("@@ line {} @@\n", line));
+            }
+            let (prefix, text) = match change {
+                Change::Same(text) => (' ', text),
+                Change::Removed(text) => ('-', text),
+                Change::Added(text) => ('+', text),
+            };
+            diff.push_str(&format!("{} {}\n", prefix, text));
+            skipped = false;
+        } else {
+            skipped = true;
+        }
+        if !matches!(change, Change::Added(_)) {
+            line += 1;
+        }
+    }
+    diff
+}
+
+fn path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("src")
+        .join("snapshots")
+        .join(format!("{}.snap", name))
+}
+
+/// Fails with a diff when `actual` differs from the snapshot called `name`.
+#[track_caller]
+pub fn assert_snapshot(name: &str, actual: &str) {
+    let path = path(name);
+    let update = std::env::var_os("UPDATE_SNAPSHOTS").is_some();
+    match fs::read_to_string(&path) {
+        Ok(expected) if expected == actual => (),
+        _ if update => {
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(&path, actual).unwrap();
+        }
+        Ok(expected) => panic!(
+            "snapshot {} changed, rerun with UPDATE_SNAPSHOTS=1 if that is intended\n{}",
+            name,
+            diff(&expected, actual)
+        ),
+        Err(err) => panic!(
+            "could not read snapshot {} ({}), rerun with UPDATE_SNAPSHOTS=1 to create it",
+            name, err
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff() {
+        let old = "a\nb\nc\nd\ne\nf\ng\nh\ni\nj\n";
+        let new = "a\nb\nc\nD\ne\nf\ng\nh\ni\nj\nk\n";
+        assert_eq!(
+            diff(old, new),
+            "@@ line 2 @@
+  b
+  c
+- d
++ D
+  e
+  f
+@@ line 9 @@
+  i
+  j
++ k
+"
+        );
+        assert_eq!(diff(old, old), "");
+    }
+}
@@ -0,0 +1,127 @@
+use crate::utils::cancel::{CancelToken, Cancelled};
+use crate::utils::parse::{ParseError, ParseResult, Span};
+use std::fmt;
+use std::io::{self, BufRead};
+
+#[derive(Debug)]
+pub enum StreamError {
+    Io(io::Error),
+    Parse(ParseError),
+    Cancelled,
+}
+
+impl fmt::Display for StreamError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StreamError::Io(err) => write!(f, "could not read input: {}", err),
+            StreamError::Parse(err) => write!(f, "{}", err),
+            StreamError::Cancelled => write!(f, "{}", Cancelled),
+        }
+    }
+}
+
+impl std::error::Error for StreamError {}
+
+impl From<io::Error> for StreamError {
+    fn from(err: io::Error) -> Self {
+        StreamError::Io(err)
+    }
+}
+
+impl From<Cancelled> for StreamError {
+    fn from(_: Cancelled) -> Self {
+        StreamError::Cancelled
+    }
+}
+
+/// Reads an input one line at a time, for inputs that do not fit in memory. Only the current
+/// line is kept, its buffer is reused for the next one.
+pub struct LineSource<'a, R> {
+    reader: R,
+    cancel: &'a CancelToken,
+    buffer: String,
+    line: usize,
+}
+
+impl<'a, R: BufRead> LineSource<'a, R> {
+    /// Reading stops with an error once the token is cancelled.
+    pub fn new(reader: R, cancel: &'a CancelToken) -> Self {
+        Self {
+            reader,
+            cancel,
+            buffer: String::new(),
+            line: 0,
+        }
+    }
+
+    /// The next line without its line break, `None` at the end of the input.
+    pub fn next_line(&mut self) -> Option<Result<&str, StreamError>> {
+        if let Err(err) = self.cancel.check() {
+            return Some(Err(err.into()));
+        }
+        self.buffer.clear();
+        match self.reader.read_line(&mut self.buffer) {
+            Ok(0) => None,
+            Ok(_) => {
+                self.line += 1;
+                let line = self.buffer.strip_suffix('\n').unwrap_or(&self.buffer);
+                Some(Ok(line.strip_suffix('\r').unwrap_or(line)))
+            }
+            Err(err) => Some(Err(err.into())),
+        }
+    }
+
+    /// Parses every line with `item`, errors point at the line in the whole input.
+    pub fn parse<T: 'a>(
+        mut self,
+        item: impl Fn(Span) -> ParseResult<T> + 'a,
+    ) -> impl Iterator<Item = Result<T, StreamError>> + 'a
+    where
+        R: 'a,
+    {
+        std::iter::from_fn(move || {
+            let line = match self.next_line()? {
+                Ok(line) => line,
+                Err(err) => return Some(Err(err)),
+            };
+            Some(item(Span::new(line)).map_err(|mut err| {
+                err.line = self.line;
+                StreamError::Parse(err)
+            }))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::parse::integer;
+
+    #[test]
+    fn test_next_line() {
+        let cancel = CancelToken::new();
+        let mut source = LineSource::new("a\r\nb\n\nc".as_bytes(), &cancel);
+        let mut lines = Vec::new();
+        while let Some(line) = source.next_line() {
+            lines.push(line.unwrap().to_owned());
+        }
+        assert_eq!(lines, ["a", "b", "", "c"]);
+    }
+
+    #[test]
+    fn test_parse() {
+        let cancel = CancelToken::new();
+        let numbers = LineSource::new("1\n2\n3\n".as_bytes(), &cancel).parse(integer::<u8>);
+        assert_eq!(numbers.map(Result::unwrap).collect::<Vec<_>>(), [1, 2, 3]);
+        let mut numbers = LineSource::new("1\n2\nx\n4".as_bytes(), &cancel).parse(integer::<u8>);
+        assert!(matches!(numbers.nth(2), Some(Err(StreamError::Parse(err))) if err.line == 3));
+    }
+
+    #[test]
+    fn test_cancel() {
+        let cancel = CancelToken::new();
+        cancel.cancel();
+        let mut numbers = LineSource::new("1\n2".as_bytes(), &cancel).parse(integer::<u8>);
+        assert!(matches!(numbers.next(), Some(Err(StreamError::Cancelled))));
+    }
+}
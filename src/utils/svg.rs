@@ -0,0 +1,88 @@
+use std::fmt::{self, Write};
+
+/// A minimal SVG document builder, enough to draw puzzle grids without any dependencies.
+#[derive(Debug, Clone)]
+pub struct Svg {
+    width: u32,
+    height: u32,
+    elements: String,
+}
+
+impl Svg {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            elements: String::new(),
+        }
+    }
+
+    pub fn rect(&mut self, x: u32, y: u32, width: u32, height: u32, fill: &str) {
+        let _ = write!(
+            self.elements,
+            r#"<rect x="{}" y="{}" width="{}" height="{}" fill="{}"/>"#,
+            x, y, width, height, fill
+        );
+    }
+
+    pub fn line(&mut self, from: (u32, u32), to: (u32, u32), stroke: &str) {
+        let _ = write!(
+            self.elements,
+            r#"<line x1="{}" y1="{}" x2="{}" y2="{}" stroke="{}"/>"#,
+            from.0, from.1, to.0, to.1, stroke
+        );
+    }
+
+    pub fn polyline(&mut self, points: &[(u32, u32)], stroke: &str) {
+        let points = points
+            .iter()
+            .map(|(x, y)| format!("{},{}", x, y))
+            .collect::<Vec<_>>()
+            .join(" ");
+        let _ = write!(
+            self.elements,
+            r#"<polyline points="{}" fill="none" stroke="{}"/>"#,
+            points, stroke
+        );
+    }
+
+    pub fn text(&mut self, x: u32, y: u32, text: &str, fill: &str) {
+        let text = text
+            .replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;");
+        let _ = write!(
+            self.elements,
+            r#"<text x="{}" y="{}" fill="{}">{}</text>"#,
+            x, y, fill, text
+        );
+    }
+}
+
+impl fmt::Display for Svg {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 {} {}" width="{}" height="{}">{}</svg>"#,
+            self.width, self.height, self.width, self.height, self.elements
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display() {
+        let mut svg = Svg::new(2, 3);
+        svg.rect(0, 1, 1, 1, "#000");
+        svg.line((0, 0), (2, 3), "red");
+        svg.polyline(&[(0, 0), (1, 2)], "blue");
+        svg.text(1, 1, "a<b", "red");
+        assert_eq!(
+            svg.to_string(),
+            r##"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 2 3" width="2" height="3"><rect x="0" y="1" width="1" height="1" fill="#000"/><line x1="0" y1="0" x2="2" y2="3" stroke="red"/><polyline points="0,0 1,2" fill="none" stroke="blue"/><text x="1" y="1" fill="red">a&lt;b</text></svg>"##
+        );
+    }
+}
@@ -0,0 +1,59 @@
+use std::process::{Command, Stdio};
+
+pub const CLEAR_SCREEN: &str = "\x1b[2J\x1b[H";
+pub const HIDE_CURSOR: &str = "\x1b[?25l";
+pub const SHOW_CURSOR: &str = "\x1b[?25h";
+/// Switches to a separate screen that is restored when leaving it, like full screen programs do.
+pub const ENTER_ALTERNATE_SCREEN: &str = "\x1b[?1049h";
+pub const LEAVE_ALTERNATE_SCREEN: &str = "\x1b[?1049l";
+
+/// Puts the terminal in non-canonical mode for the lifetime of the guard, so single key presses
+/// can be read without waiting for a newline.
+pub struct RawMode;
+
+impl RawMode {
+    pub fn enable() -> Self {
+        stty(&["-icanon", "-echo", "min", "1"]);
+        RawMode
+    }
+}
+
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        stty(&["icanon", "echo"]);
+    }
+}
+
+fn stty(args: &[&str]) -> Option<String> {
+    let output = Command::new("stty")
+        .args(args)
+        .stdin(Stdio::inherit())
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// The terminal's (columns, rows), None if stdin is not a terminal.
+pub fn size() -> Option<(usize, usize)> {
+    parse_size(&stty(&["size"])?)
+}
+
+fn parse_size(output: &str) -> Option<(usize, usize)> {
+    let (rows, columns) = output.trim().split_once(' ')?;
+    Some((columns.parse().ok()?, rows.parse().ok()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(parse_size("24 80\n"), Some((80, 24)));
+        assert_eq!(parse_size(""), None);
+    }
+}